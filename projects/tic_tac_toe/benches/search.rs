@@ -0,0 +1,138 @@
+//! Searches whole game trees, comparing `Game` with a board that checks for a
+//! win by walking every line after each move, which is how `Game` used to do
+//! it before it kept bitboards.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use tic_tac_toe::game::{Game, Player, Rules, Symbol};
+
+/// Right, down and the two diagonals.
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+/// A board that finds wins by collecting each line's squares and comparing
+/// them.
+#[derive(Clone)]
+struct ScanningBoard {
+    rules: Rules,
+    board: Vec<Symbol>,
+    current_player: Player,
+}
+
+impl ScanningBoard {
+    fn new(game: &Game) -> ScanningBoard {
+        ScanningBoard {
+            rules: game.rules(),
+            board: game.board().to_vec(),
+            current_player: game.current_player(),
+        }
+    }
+
+    fn available_moves(&self) -> Vec<usize> {
+        (0..self.board.len())
+            .filter(|index| self.board[*index] == Symbol::Empty)
+            .collect()
+    }
+
+    fn play(&mut self, index: usize) {
+        self.board[index] = self.current_player.into();
+        self.toggle_player();
+    }
+
+    fn undo(&mut self, index: usize) {
+        self.board[index] = Symbol::Empty;
+        self.toggle_player();
+    }
+
+    fn toggle_player(&mut self) {
+        self.current_player = self.current_player.other();
+    }
+
+    fn get_winner(&self) -> Option<Symbol> {
+        let (width, height) = (self.rules.width() as isize, self.rules.height() as isize);
+        let length = self.rules.win_length() as isize;
+
+        for start in 0..self.board.len() as isize {
+            let (row, col) = (start / width, start % width);
+            for (row_step, col_step) in DIRECTIONS.iter() {
+                let line: Vec<Symbol> = (0..length)
+                    .map(|step| (row + row_step * step, col + col_step * step))
+                    .take_while(|(r, c)| *r < height && *c >= 0 && *c < width)
+                    .map(|(r, c)| self.board[(r * width + c) as usize])
+                    .collect();
+
+                if line.len() == length as usize
+                    && line[0] != Symbol::Empty
+                    && line.iter().all(|symbol| *symbol == line[0])
+                {
+                    return Some(line[0]);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// The best result for the player to move: 1 for a win, 0 a draw and -1 a
+/// loss, searching every move without remembering positions.
+fn solve_game(game: &mut Game) -> i32 {
+    if game.get_winner().is_some() {
+        return -1;
+    }
+
+    let mut best = None;
+    for index in game.available_moves() {
+        game.play(index).ok();
+        let score = -solve_game(game);
+        game.undo();
+        best = best.max(Some(score));
+    }
+
+    best.unwrap_or(0)
+}
+
+fn solve_scanning(board: &mut ScanningBoard) -> i32 {
+    if board.get_winner().is_some() {
+        return -1;
+    }
+
+    let mut best = None;
+    for index in board.available_moves() {
+        board.play(index);
+        let score = -solve_scanning(board);
+        board.undo(index);
+        best = best.max(Some(score));
+    }
+
+    best.unwrap_or(0)
+}
+
+fn positions() -> Vec<(&'static str, Game)> {
+    let mut four_by_four = Game::with_rules(4, 4, 4).unwrap();
+    for index in [5, 6, 9, 10, 0, 15, 3] {
+        four_by_four.play(index).ok();
+    }
+
+    vec![
+        ("3x3:3", Game::start()),
+        ("4x4:4 after 7 moves", four_by_four),
+    ]
+}
+
+fn full_tree_search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("full tree search");
+    group.sample_size(10);
+
+    for (name, game) in positions() {
+        group.bench_with_input(BenchmarkId::new("bitboard", name), &game, |b, game| {
+            b.iter(|| solve_game(&mut black_box(game.clone())))
+        });
+        group.bench_with_input(BenchmarkId::new("line scan", name), &game, |b, game| {
+            b.iter(|| solve_scanning(&mut black_box(ScanningBoard::new(game))))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, full_tree_search);
+criterion_main!(benches);
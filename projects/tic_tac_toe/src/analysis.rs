@@ -0,0 +1,392 @@
+//! Solves positions completely, working out the result of every move with
+//! perfect play from both sides.
+
+use crate::coord::Coord;
+use crate::game::{Game, Player, Rules, Symbol};
+use crate::symmetry::{self, Canonical};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Positions with more empty squares than this take too long to solve.
+pub const MAX_EMPTY_SQUARES: usize = 12;
+
+/// The result for the player to move.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Verdict {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// A verdict along with how many more moves the game lasts with perfect
+/// play. The winner takes the quickest win and the loser holds out longest.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Evaluation {
+    pub verdict: Verdict,
+    pub distance: u32,
+}
+
+impl Evaluation {
+    /// How good this is for the player to move, higher being better.
+    fn score(self) -> i64 {
+        let distance = i64::from(self.distance);
+        match self.verdict {
+            Verdict::Win => 1000 - distance,
+            Verdict::Draw => 0,
+            Verdict::Loss => distance - 1000,
+        }
+    }
+
+    /// The same result seen by the other player one move earlier.
+    fn before_move(self) -> Evaluation {
+        let verdict = match self.verdict {
+            Verdict::Win => Verdict::Loss,
+            Verdict::Draw => Verdict::Draw,
+            Verdict::Loss => Verdict::Win,
+        };
+
+        Evaluation {
+            verdict,
+            distance: self.distance + 1,
+        }
+    }
+
+    /// Describes the result in terms of who wins, given who is to move.
+    pub fn describe(self, to_move: Player) -> String {
+        let other = to_move.other();
+        let moves = if self.distance == 1 { "move" } else { "moves" };
+
+        match self.verdict {
+            Verdict::Draw => String::from("draw"),
+            Verdict::Win => format!("{} wins in {} {}", to_move, self.distance, moves),
+            Verdict::Loss => format!("{} wins in {} {}", other, self.distance, moves),
+        }
+    }
+}
+
+impl Ord for Evaluation {
+    fn cmp(&self, other: &Evaluation) -> Ordering {
+        self.score().cmp(&other.score())
+    }
+}
+
+impl PartialOrd for Evaluation {
+    fn partial_cmp(&self, other: &Evaluation) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Solves positions, remembering every position it has seen so positions
+/// reached by different move orders, or that are rotations or reflections of
+/// each other, are only solved once.
+#[derive(Default)]
+pub struct Solver {
+    /// Results keyed on the canonical board, along with the best move in
+    /// the canonical board's coordinates.
+    table: HashMap<(Rules, Vec<Symbol>, Player), (Evaluation, usize)>,
+}
+
+impl Solver {
+    pub fn new() -> Solver {
+        Solver::default()
+    }
+
+    /// How many unfinished positions have been solved so far.
+    pub fn positions(&self) -> usize {
+        self.table.len()
+    }
+
+    /// The result of the position for the player to move.
+    pub fn evaluate(&mut self, game: &Game) -> Evaluation {
+        // The player who just moved can't have lost, so a winner means the
+        // player to move has.
+        if game.get_winner().is_some() {
+            return Evaluation {
+                verdict: Verdict::Loss,
+                distance: 0,
+            };
+        }
+
+        let moves = game.available_moves();
+        if moves.is_empty() {
+            return Evaluation {
+                verdict: Verdict::Draw,
+                distance: 0,
+            };
+        }
+
+        let canonical = Canonical::of(game);
+        let key = (game.rules(), canonical.board.clone(), game.current_player());
+        if let Some((evaluation, _)) = self.table.get(&key) {
+            return *evaluation;
+        }
+
+        let best = moves
+            .into_iter()
+            .filter_map(|index| Some((self.evaluate_move(game, index)?, index)))
+            .max_by(|(a, a_index), (b, b_index)| a.cmp(b).then(b_index.cmp(a_index)));
+        let (evaluation, index) = match best {
+            Some(best) => best,
+            None => {
+                let draw = Evaluation {
+                    verdict: Verdict::Draw,
+                    distance: 0,
+                };
+                return draw;
+            }
+        };
+        if let Some(moved) = canonical.move_to_canonical(index) {
+            self.table.insert(key, (evaluation, moved));
+        }
+
+        evaluation
+    }
+
+    /// A move that gets the best result for the player to move, or `None`
+    /// once the game is over.
+    pub fn best_move(&mut self, game: &Game) -> Option<usize> {
+        self.evaluate(game);
+
+        let canonical = Canonical::of(game);
+        let key = (game.rules(), canonical.board.clone(), game.current_player());
+        let (_, index) = self.table.get(&key)?;
+
+        canonical.move_from_canonical(*index)
+    }
+
+    /// The result for the player to move of playing `index`, or `None` if
+    /// it isn't a legal move.
+    pub fn evaluate_move(&mut self, game: &Game, index: usize) -> Option<Evaluation> {
+        let mut next = game.clone();
+        next.play(index).ok()?;
+
+        Some(self.evaluate(&next).before_move())
+    }
+
+    /// Every legal move with its result, best first. Moves that are equally
+    /// good stay in board order.
+    pub fn analyze(&mut self, game: &Game) -> Vec<(usize, Evaluation)> {
+        if game.get_winner().is_some() {
+            return Vec::new();
+        }
+
+        let mut moves: Vec<(usize, Evaluation)> = game
+            .available_moves()
+            .into_iter()
+            .filter_map(|index| Some((index, self.evaluate_move(game, index)?)))
+            .collect();
+        moves.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        moves
+    }
+}
+
+/// A printable report of a position: the result with perfect play and the
+/// result of each move.
+pub fn report(game: &Game) -> Vec<String> {
+    let mut solver = Solver::new();
+    let to_move = game.current_player();
+
+    if let Some(winner) = game.get_winner() {
+        return vec![format!("{} has won", winner)];
+    }
+    if game.available_moves().is_empty() {
+        return vec![String::from("The game is a draw")];
+    }
+
+    let mut lines = vec![format!(
+        "{} to play, {} with perfect play",
+        to_move,
+        solver.evaluate(game).describe(to_move)
+    )];
+    for (index, evaluation) in solver.analyze(game) {
+        lines.push(format!(
+            "  {:<4} {:>3}  {}",
+            Coord::from_index(index, game.rules()).to_string(),
+            index,
+            evaluation.describe(to_move)
+        ));
+    }
+    if let Some(index) = solver.best_move(game) {
+        lines.push(format!(
+            "Best move: {} ({})",
+            Coord::from_index(index, game.rules()),
+            index
+        ));
+    }
+    if let Some(hash) = symmetry::canonical_hash(game) {
+        lines.push(format!("Canonical position: {}", hash));
+    }
+    lines.push(format!("{} positions solved", solver.positions()));
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::collections::HashSet;
+
+    fn win(distance: u32) -> Evaluation {
+        Evaluation {
+            verdict: Verdict::Win,
+            distance,
+        }
+    }
+
+    fn draw(distance: u32) -> Evaluation {
+        Evaluation {
+            verdict: Verdict::Draw,
+            distance,
+        }
+    }
+
+    fn loss(distance: u32) -> Evaluation {
+        Evaluation {
+            verdict: Verdict::Loss,
+            distance,
+        }
+    }
+
+    #[test]
+    fn the_empty_board_is_a_draw() {
+        let mut solver = Solver::new();
+
+        assert_eq!(solver.evaluate(&Game::start()), draw(9));
+        assert!(solver
+            .analyze(&Game::start())
+            .iter()
+            .all(|(_, evaluation)| evaluation.verdict == Verdict::Draw));
+    }
+
+    #[test]
+    fn every_reachable_position_is_solved_once() {
+        // 765 positions can be reached once rotations and reflections are
+        // set aside, 138 of them finished games.
+        let mut solver = Solver::new();
+        solver.evaluate(&Game::start());
+
+        assert_eq!(solver.positions(), 765 - 138);
+    }
+
+    #[rstest]
+    // X can complete the top row.
+    #[case("XX./OO./...", win(1))]
+    // O has to take the bottom left corner to stop X, then X fills the last
+    // square.
+    #[case("XOX/XOO/.X.", draw(2))]
+    // O threatens the top row and left column, so X can only block one.
+    #[case("OO./OXX/.X.", loss(2))]
+    fn positions_are_solved(#[case] position: &str, #[case] expected: Evaluation) {
+        let game: Game = position.parse().unwrap();
+
+        assert_eq!(Solver::new().evaluate(&game), expected);
+    }
+
+    #[test]
+    fn opposite_corners_must_be_answered_on_an_edge() {
+        // Taking a corner lets X fork with the last corner.
+        let game: Game = "X../.O./..X".parse().unwrap();
+        let mut solver = Solver::new();
+
+        for (index, evaluation) in solver.analyze(&game) {
+            let expected = if [2, 6].contains(&index) {
+                Verdict::Loss
+            } else {
+                Verdict::Draw
+            };
+            assert_eq!(evaluation.verdict, expected, "square {}", index);
+        }
+    }
+
+    // Checks the best move from every position reachable from `game` gets
+    // the position's result, so the moves stored for canonical boards map
+    // back correctly.
+    fn assert_best_moves(solver: &mut Solver, game: &Game, seen: &mut HashSet<Vec<Symbol>>) {
+        if !seen.insert(game.board().to_vec()) {
+            return;
+        }
+
+        let best = match solver.best_move(game) {
+            Some(best) => best,
+            None => return,
+        };
+        assert_eq!(
+            solver.evaluate_move(game, best),
+            Some(solver.evaluate(game)),
+            "{:?}",
+            game.board()
+        );
+
+        for index in game.available_moves() {
+            let mut next = game.clone();
+            next.play(index).ok();
+            assert_best_moves(solver, &next, seen);
+        }
+    }
+
+    #[test]
+    fn best_moves_get_the_best_result() {
+        let mut solver = Solver::new();
+        assert_best_moves(&mut solver, &Game::start(), &mut HashSet::new());
+
+        assert_eq!(solver.best_move(&"XXX/OO./...".parse().unwrap()), None);
+    }
+
+    #[test]
+    fn moves_are_ordered_best_first() {
+        let game: Game = "XX./OO./...".parse().unwrap();
+        let moves = Solver::new().analyze(&game);
+
+        assert_eq!(moves[0], (2, win(1)));
+        assert!(moves.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    }
+
+    #[test]
+    fn quicker_wins_and_slower_losses_are_better() {
+        assert!(win(1) > win(3));
+        assert!(win(9) > draw(1));
+        assert!(draw(1) > loss(9));
+        assert!(loss(5) > loss(1));
+    }
+
+    #[test]
+    fn finished_games_have_no_moves() {
+        let game: Game = "XXX/OO./...".parse().unwrap();
+
+        assert_eq!(Solver::new().evaluate(&game), loss(0));
+        assert!(Solver::new().analyze(&game).is_empty());
+    }
+
+    #[test]
+    fn illegal_moves_are_not_evaluated() {
+        let game: Game = "X../.../...".parse().unwrap();
+
+        assert_eq!(Solver::new().evaluate_move(&game, 0), None);
+        assert_eq!(Solver::new().evaluate_move(&game, 9), None);
+    }
+
+    #[test]
+    fn other_rules_can_be_solved() {
+        // In a row of four with two to win, X takes a middle square and
+        // then has two ways to finish.
+        let game = Game::with_rules(4, 1, 2).unwrap();
+
+        assert_eq!(Solver::new().evaluate(&game), win(3));
+    }
+
+    #[test]
+    fn reports_describe_each_move() {
+        let game: Game = "XX./OO./...".parse().unwrap();
+        let lines = report(&game);
+
+        assert_eq!(lines[0], "X to play, X wins in 1 move with perfect play");
+        assert_eq!(lines[1], "  C1     2  X wins in 1 move");
+        assert_eq!(lines.len(), 9);
+        assert_eq!(lines[6], "Best move: C1 (2)");
+        assert!(lines[7].starts_with("Canonical position: "));
+        assert!(lines[8].ends_with("positions solved"));
+        assert_eq!(report(&"XXX/OO./...".parse().unwrap()), ["X has won"]);
+    }
+}
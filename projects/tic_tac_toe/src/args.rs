@@ -0,0 +1,279 @@
+use crate::net::DEFAULT_ADDRESS;
+use tic_tac_toe::computer::Difficulty;
+use tic_tac_toe::variant::Kind;
+
+/// What the binary should do, chosen by an optional subcommand.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub enum Mode {
+    #[default]
+    Local,
+    Serve(String),
+    Join(String),
+    Analyze(String),
+    Replay(String),
+    /// Writes an opening book for the chosen rules to the file.
+    GenerateBook(String),
+}
+
+#[derive(Default, PartialEq, Debug)]
+pub struct Args {
+    pub mode: Mode,
+    pub difficulty: Option<Difficulty>,
+    pub seed: Option<u64>,
+    pub size: Option<(usize, usize)>,
+    pub win_length: Option<usize>,
+    pub colour: bool,
+    pub tui: bool,
+    pub leaderboard: Option<String>,
+    pub variant: Kind,
+    pub book: Option<String>,
+    pub depth: Option<usize>,
+    pub best_of: Option<u32>,
+}
+
+impl Args {
+    /// Parses the command line arguments, not including the program name.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Args, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter().peekable();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "serve" | "join" if parsed.mode == Mode::Local => {
+                    let address = match args.peek() {
+                        Some(next) if !next.starts_with("--") => args.next().unwrap_or_default(),
+                        _ => String::from(DEFAULT_ADDRESS),
+                    };
+                    parsed.mode = if arg == "serve" {
+                        Mode::Serve(address)
+                    } else {
+                        Mode::Join(address)
+                    };
+                }
+                "--difficulty" => {
+                    let value = args.next().ok_or("--difficulty needs a value")?;
+                    parsed.difficulty = Some(value.parse()?);
+                }
+                "--seed" => {
+                    let value = args.next().ok_or("--seed needs a value")?;
+                    let seed = value
+                        .parse()
+                        .map_err(|_| format!("'{}' is not a valid seed", value))?;
+                    parsed.seed = Some(seed);
+                }
+                "--size" => {
+                    let value = args.next().ok_or("--size needs a value")?;
+                    parsed.size = Some(parse_size(&value)?);
+                }
+                "--win" => {
+                    let value = args.next().ok_or("--win needs a value")?;
+                    let win_length = value
+                        .parse()
+                        .map_err(|_| format!("'{}' is not a valid win length", value))?;
+                    parsed.win_length = Some(win_length);
+                }
+                "analyze" | "analyse" if parsed.mode == Mode::Local => {
+                    let position = args.next().ok_or("analyze needs a position")?;
+                    parsed.mode = Mode::Analyze(position);
+                }
+                "replay" if parsed.mode == Mode::Local => {
+                    let path = args.next().ok_or("replay needs a file")?;
+                    parsed.mode = Mode::Replay(path);
+                }
+                "book" if parsed.mode == Mode::Local => {
+                    let path = args.next().ok_or("book needs a file to write")?;
+                    parsed.mode = Mode::GenerateBook(path);
+                }
+                "--book" => {
+                    let value = args.next().ok_or("--book needs a file")?;
+                    parsed.book = Some(value);
+                }
+                "--depth" => {
+                    let value = args.next().ok_or("--depth needs a value")?;
+                    let depth = value
+                        .parse()
+                        .map_err(|_| format!("'{}' is not a valid depth", value))?;
+                    parsed.depth = Some(depth);
+                }
+                "--best-of" => {
+                    let value = args.next().ok_or("--best-of needs a number of games")?;
+                    let games = match value.parse() {
+                        Ok(games) if games > 0 => games,
+                        _ => return Err(format!("'{}' is not a number of games", value)),
+                    };
+                    parsed.best_of = Some(games);
+                }
+                "--colour" | "--color" => parsed.colour = true,
+                "--tui" => parsed.tui = true,
+                "--leaderboard" => {
+                    let value = args.next().ok_or("--leaderboard needs a file")?;
+                    parsed.leaderboard = Some(value);
+                }
+                "--variant" => {
+                    let value = args.next().ok_or("--variant needs a name")?;
+                    parsed.variant = value.parse()?;
+                }
+                other => return Err(format!("Unknown argument '{}'", other)),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+/// Parses a board size written as `<width>x<height>`, e.g. `15x15`.
+fn parse_size(value: &str) -> Result<(usize, usize), String> {
+    let mut parts = value.splitn(2, 'x');
+
+    let width = parts.next().and_then(|width| width.parse().ok());
+    let height = parts.next().and_then(|height| height.parse().ok());
+
+    match (width, height) {
+        (Some(width), Some(height)) => Ok((width, height)),
+        _ => Err(format!(
+            "'{}' is not a valid size, expected e.g. 4x4",
+            value
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_uses_defaults() {
+        assert_eq!(parse(&[]), Ok(Args::default()));
+    }
+
+    #[test]
+    fn difficulty_and_seed_are_parsed() {
+        assert_eq!(
+            parse(&["--difficulty", "easy", "--seed", "7"]),
+            Ok(Args {
+                difficulty: Some(Difficulty::Easy),
+                seed: Some(7),
+                ..Args::default()
+            })
+        );
+    }
+
+    #[test]
+    fn board_size_and_win_length_are_parsed() {
+        assert_eq!(
+            parse(&["--size", "15x15", "--win", "5"]),
+            Ok(Args {
+                size: Some((15, 15)),
+                win_length: Some(5),
+                ..Args::default()
+            })
+        );
+    }
+
+    #[test]
+    fn colour_can_be_spelt_either_way() {
+        assert!(parse(&["--colour"]).unwrap().colour);
+        assert!(parse(&["--color"]).unwrap().colour);
+    }
+
+    #[test]
+    fn full_screen_mode_is_opt_in() {
+        assert!(!parse(&[]).unwrap().tui);
+        assert!(parse(&["--tui"]).unwrap().tui);
+    }
+
+    #[test]
+    fn leaderboard_file_can_be_chosen() {
+        assert_eq!(
+            parse(&["--leaderboard", "scores.txt"]).unwrap().leaderboard,
+            Some(String::from("scores.txt"))
+        );
+    }
+
+    #[test]
+    fn variants_are_chosen_by_name() {
+        assert_eq!(parse(&[]).unwrap().variant, Kind::Standard);
+        assert_eq!(
+            parse(&["--variant", "misere"]).unwrap().variant,
+            Kind::Misere
+        );
+        assert!(parse(&["--variant", "chess"]).is_err());
+        assert!(parse(&["--variant"]).is_err());
+    }
+
+    #[test]
+    fn series_length_is_parsed() {
+        assert_eq!(parse(&[]).unwrap().best_of, None);
+        assert_eq!(parse(&["--best-of", "5"]).unwrap().best_of, Some(5));
+        assert!(parse(&["--best-of", "0"]).is_err());
+        assert!(parse(&["--best-of", "three"]).is_err());
+        assert!(parse(&["--best-of"]).is_err());
+    }
+
+    #[test]
+    fn network_modes_take_an_optional_address() {
+        assert_eq!(parse(&[]).unwrap().mode, Mode::Local);
+        assert_eq!(
+            parse(&["serve"]).unwrap().mode,
+            Mode::Serve(String::from(DEFAULT_ADDRESS))
+        );
+        assert_eq!(
+            parse(&["join", "192.168.0.2:7878"]).unwrap().mode,
+            Mode::Join(String::from("192.168.0.2:7878"))
+        );
+
+        let args = parse(&["serve", "--size", "4x4"]).unwrap();
+        assert_eq!(args.mode, Mode::Serve(String::from(DEFAULT_ADDRESS)));
+        assert_eq!(args.size, Some((4, 4)));
+    }
+
+    #[test]
+    fn analyze_takes_a_position() {
+        assert_eq!(
+            parse(&["analyze", "X../.O./..."]).unwrap().mode,
+            Mode::Analyze(String::from("X../.O./..."))
+        );
+        assert!(parse(&["analyze"]).is_err());
+    }
+
+    #[test]
+    fn replay_takes_a_file() {
+        assert_eq!(
+            parse(&["replay", "game.txt", "--colour"]).unwrap().mode,
+            Mode::Replay(String::from("game.txt"))
+        );
+        assert!(parse(&["replay"]).is_err());
+    }
+
+    #[test]
+    fn books_can_be_written_and_used() {
+        let args = parse(&["book", "openings.book", "--size", "4x4", "--depth", "3"]).unwrap();
+        assert_eq!(args.mode, Mode::GenerateBook(String::from("openings.book")));
+        assert_eq!(args.depth, Some(3));
+
+        assert_eq!(
+            parse(&["--book", "openings.book"]).unwrap().book,
+            Some(String::from("openings.book"))
+        );
+        assert!(parse(&["book"]).is_err());
+        assert!(parse(&["--book"]).is_err());
+        assert!(parse(&["--depth", "deep"]).is_err());
+    }
+
+    #[test]
+    fn invalid_arguments_are_reported() {
+        assert!(parse(&["--difficulty"]).is_err());
+        assert!(parse(&["--difficulty", "impossible"]).is_err());
+        assert!(parse(&["--seed", "abc"]).is_err());
+        assert!(parse(&["--size", "15"]).is_err());
+        assert!(parse(&["--size", "ax3"]).is_err());
+        assert!(parse(&["--win", "-1"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+        assert!(parse(&["--leaderboard"]).is_err());
+        assert!(parse(&["serve", "localhost:7878", "join"]).is_err());
+    }
+}
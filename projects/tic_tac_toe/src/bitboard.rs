@@ -0,0 +1,214 @@
+//! One bit per square, so a player's pieces fit in a few machine words and
+//! checking for a win is a handful of mask comparisons rather than a walk
+//! over the board.
+
+use crate::game::{Rules, DIRECTIONS};
+use std::fmt;
+
+/// A player's pieces, with as many words as the board needs.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Bitboard(Vec<u64>);
+
+impl Bitboard {
+    /// An empty board with room for `squares` squares.
+    pub fn new(squares: usize) -> Bitboard {
+        Bitboard(vec![0; squares.div_ceil(64)])
+    }
+
+    pub fn set(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    pub fn clear(&mut self, index: usize) {
+        self.0[index / 64] &= !(1 << (index % 64));
+    }
+
+    /// Whether every square in `mask` is also in this board.
+    pub fn contains_all(&self, mask: &Mask) -> bool {
+        mask.0
+            .iter()
+            .all(|(word, bits)| self.0[*word] & bits == *bits)
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.iter().map(|bits| bits.count_ones() as usize).sum()
+    }
+}
+
+/// A few squares, such as a line, keeping only the words they're in. A line
+/// touches at most one word per square however big the board is.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Mask(Vec<(usize, u64)>);
+
+impl Mask {
+    pub fn set(&mut self, index: usize) {
+        let (word, bit) = (index / 64, 1 << (index % 64));
+        match self.0.iter_mut().find(|(existing, _)| *existing == word) {
+            Some((_, bits)) => *bits |= bit,
+            None => self.0.push((word, bit)),
+        }
+    }
+}
+
+/// Every run of `win_length` squares on the board, worked out once for a set
+/// of rules.
+#[derive(PartialEq)]
+pub struct WinLines {
+    lines: Vec<Mask>,
+    /// For each square, the lines that pass through it.
+    through: Vec<Vec<usize>>,
+}
+
+impl WinLines {
+    pub fn new(rules: Rules) -> WinLines {
+        let (width, height) = (rules.width() as isize, rules.height() as isize);
+        let length = rules.win_length() as isize;
+        let mut lines = Vec::new();
+        let mut through = vec![Vec::new(); rules.squares()];
+
+        for start in 0..rules.squares() as isize {
+            let (row, col) = (start / width, start % width);
+
+            for (row_step, col_step) in DIRECTIONS.iter() {
+                let (last_row, last_col) =
+                    (row + row_step * (length - 1), col + col_step * (length - 1));
+                if last_row >= height || last_col < 0 || last_col >= width {
+                    continue;
+                }
+
+                let mut line = Mask::default();
+                for step in 0..length {
+                    let index = ((row + row_step * step) * width + col + col_step * step) as usize;
+                    line.set(index);
+                    through[index].push(lines.len());
+                }
+                lines.push(line);
+            }
+        }
+
+        WinLines { lines, through }
+    }
+
+    /// Whether `pieces` fill any line.
+    pub fn any_complete(&self, pieces: &Bitboard) -> bool {
+        self.lines.iter().any(|line| pieces.contains_all(line))
+    }
+
+    /// Whether `pieces` fill a line through `index`, which is all that needs
+    /// checking after a move there.
+    pub fn completed_through(&self, pieces: &Bitboard, index: usize) -> bool {
+        self.through[index]
+            .iter()
+            .any(|line| pieces.contains_all(&self.lines[*line]))
+    }
+}
+
+impl fmt::Debug for WinLines {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WinLines({} lines)", self.lines.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn board(indexes: &[usize]) -> Bitboard {
+        let mut board = Bitboard::new(256);
+        for index in indexes {
+            board.set(*index);
+        }
+        board
+    }
+
+    fn mask(indexes: &[usize]) -> Mask {
+        let mut mask = Mask::default();
+        for index in indexes {
+            mask.set(*index);
+        }
+        mask
+    }
+
+    #[test]
+    fn squares_can_be_set_and_cleared_across_words() {
+        let mut pieces = board(&[0, 63, 64, 255]);
+
+        assert!(pieces.contains_all(&mask(&[63, 64, 255])));
+        assert!(!pieces.contains_all(&mask(&[1])));
+        assert_eq!(pieces.count(), 4);
+
+        pieces.clear(64);
+
+        assert!(!pieces.contains_all(&mask(&[64])));
+        assert_eq!(pieces.count(), 3);
+    }
+
+    #[test]
+    fn masks_are_contained_only_when_every_square_is_set() {
+        let pieces = board(&[0, 1, 2, 100]);
+
+        assert!(pieces.contains_all(&mask(&[0, 2, 100])));
+        assert!(!pieces.contains_all(&mask(&[0, 3])));
+        assert!(pieces.contains_all(&Mask::default()));
+    }
+
+    #[test]
+    fn boards_have_room_for_every_square() {
+        assert_eq!(Bitboard::new(1).0.len(), 1);
+        assert_eq!(Bitboard::new(64).0.len(), 1);
+        assert_eq!(Bitboard::new(65).0.len(), 2);
+        assert_eq!(Bitboard::new(10_000).0.len(), 157);
+    }
+
+    #[test]
+    fn masks_keep_one_entry_per_word() {
+        // A column of a 100 wide board, each square in its own word.
+        let column = mask(&[50, 150, 250, 350]);
+        let row = mask(&[60, 61, 62, 63, 64]);
+
+        assert_eq!(column.0.len(), 4);
+        assert_eq!(row.0, vec![(0, 0xf << 60), (1, 1)]);
+    }
+
+    #[rstest]
+    // Three rows, three columns and two diagonals.
+    #[case(3, 3, 3, 8)]
+    // Each row of four has two runs of three, and so on.
+    #[case(4, 4, 3, 24)]
+    #[case(4, 1, 2, 3)]
+    #[case(15, 15, 5, 572)]
+    fn every_line_is_found_once(
+        #[case] width: usize,
+        #[case] height: usize,
+        #[case] k: usize,
+        #[case] expected: usize,
+    ) {
+        let lines = WinLines::new(Rules::new(width, height, k).unwrap());
+
+        assert_eq!(lines.lines.len(), expected);
+    }
+
+    #[test]
+    fn lines_through_a_square_are_indexed() {
+        let lines = WinLines::new(Rules::classic());
+
+        // The centre is on both diagonals, its row and its column.
+        assert_eq!(lines.through[4].len(), 4);
+        assert_eq!(lines.through[1].len(), 2);
+        assert!(lines.completed_through(&board(&[2, 4, 6]), 4));
+        assert!(!lines.completed_through(&board(&[2, 4, 6, 0]), 0));
+        assert!(lines.any_complete(&board(&[2, 4, 6])));
+        assert!(!lines.any_complete(&board(&[0, 1, 5, 6, 8])));
+    }
+
+    #[test]
+    fn lines_do_not_wrap_around_the_edge() {
+        let lines = WinLines::new(Rules::new(4, 4, 3).unwrap());
+
+        // Across the end of a row, and down-left off the left edge.
+        assert!(!lines.any_complete(&board(&[2, 3, 4])));
+        assert!(!lines.any_complete(&board(&[1, 4, 7])));
+        assert!(lines.any_complete(&board(&[3, 6, 9])));
+    }
+}
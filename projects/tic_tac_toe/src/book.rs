@@ -0,0 +1,479 @@
+//! Opening books: the best move in every position a few moves into the game,
+//! worked out ahead of time for boards too big to search well during play.
+//! Positions that are rotations or reflections of each other share an entry.
+//!
+//! Books are saved as bytes, numbers being little endian:
+//!
+//! - `TTTB` and the format version, one byte
+//! - the width, height, win length and depth, two bytes each
+//! - the number of positions, four bytes, then for each position the player
+//!   to move (0 for X, 1 for O), the number of crosses, the number of
+//!   naughts, the squares of the crosses then the naughts and the best move,
+//!   two bytes each except the player
+//! - an FNV-1a checksum of everything before it, eight bytes
+//!
+//! Squares fit in two bytes because boards have at most `MAX_SQUARES` of
+//! them.
+
+use crate::computer;
+use crate::game::{Game, GameStatus, Player, Rules, RulesError, Symbol, MAX_SQUARES};
+use crate::symmetry::Canonical;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+/// Bumped whenever the layout changes, so older files are rejected.
+pub const FORMAT_VERSION: u8 = 1;
+
+const MAGIC: &[u8; 4] = b"TTTB";
+
+const _: () = assert!(MAX_SQUARES <= 1 << 16, "squares must fit in two bytes");
+
+// Generating a book can afford a much deeper search than a move in play.
+const SEARCH_BUDGET: usize = 20_000_000;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Book {
+    rules: Rules,
+    depth: usize,
+    /// Best moves by canonical board and player to move, the moves being
+    /// squares on the canonical board.
+    moves: HashMap<(Vec<Symbol>, Player), usize>,
+}
+
+impl Book {
+    /// Searches every position reached in at most `depth` moves, with
+    /// either player going first.
+    pub fn generate(rules: Rules, depth: usize) -> Book {
+        let mut book = Book {
+            rules,
+            depth,
+            moves: HashMap::new(),
+        };
+
+        for first in [Player::Cross, Player::Naught] {
+            book.add_positions(&Game::starting_with(rules, first));
+        }
+
+        book
+    }
+
+    fn add_positions(&mut self, game: &Game) {
+        if game.history().len() > self.depth
+            || !matches!(game.status(), GameStatus::InProgress { .. })
+        {
+            return;
+        }
+
+        let canonical = Canonical::of(game);
+        let key = (canonical.board.clone(), game.current_player());
+        if self.moves.contains_key(&key) {
+            return;
+        }
+        if let Some(best) =
+            computer::search(game, SEARCH_BUDGET).and_then(|best| canonical.move_to_canonical(best))
+        {
+            self.moves.insert(key, best);
+        }
+
+        // Positions symmetric to this one are skipped above, which is fine
+        // as their continuations are symmetric to these.
+        for index in game.available_moves() {
+            let mut next = game.clone();
+            if next.play(index).is_ok() {
+                self.add_positions(&next);
+            }
+        }
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// How many moves into the game the book covers.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The number of positions in the book.
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// The book move in `game`, or `None` if the book doesn't have the
+    /// position or was made for other rules.
+    pub fn best_move(&self, game: &Game) -> Option<usize> {
+        if game.rules() != self.rules {
+            return None;
+        }
+
+        let canonical = Canonical::of(game);
+        let best = self
+            .moves
+            .get(&(canonical.board.clone(), game.current_player()))?;
+
+        canonical.move_from_canonical(*best)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(FORMAT_VERSION);
+        for value in [
+            self.rules.width(),
+            self.rules.height(),
+            self.rules.win_length(),
+            self.depth,
+        ] {
+            bytes.extend_from_slice(&(value.min(usize::from(u16::MAX)) as u16).to_le_bytes());
+        }
+        bytes.extend_from_slice(&(self.moves.len() as u32).to_le_bytes());
+
+        // Sorted so the same book always makes the same file.
+        let mut entries: Vec<Vec<u8>> = self
+            .moves
+            .iter()
+            .map(|((board, to_move), best)| encode_entry(board, *to_move, *best))
+            .collect();
+        entries.sort();
+        for entry in entries {
+            bytes.extend_from_slice(&entry);
+        }
+
+        let checksum = fnv1a(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Reads a book saved by `to_bytes`, checking it's undamaged and was
+    /// made for `rules`.
+    pub fn from_bytes(bytes: &[u8], rules: Rules) -> Result<Book, BookError> {
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(BookError::NotABook);
+        }
+        match bytes.get(MAGIC.len()) {
+            Some(&FORMAT_VERSION) => {}
+            Some(version) => return Err(BookError::UnsupportedVersion(*version)),
+            None => return Err(BookError::Truncated),
+        }
+
+        let contents_length = bytes.len().checked_sub(8).ok_or(BookError::Truncated)?;
+        let (contents, checksum) = bytes.split_at(contents_length);
+        if contents.len() <= MAGIC.len() {
+            return Err(BookError::Truncated);
+        }
+        let mut checksum_bytes = [0; 8];
+        checksum_bytes.copy_from_slice(checksum);
+        if fnv1a(contents) != u64::from_le_bytes(checksum_bytes) {
+            return Err(BookError::ChecksumMismatch);
+        }
+
+        let mut reader = Reader {
+            bytes: &contents[MAGIC.len() + 1..],
+        };
+        let width = reader.u16()?;
+        let height = reader.u16()?;
+        let win_length = reader.u16()?;
+        let depth = reader.u16()?;
+        let book_rules = Rules::new(width, height, win_length).map_err(BookError::InvalidRules)?;
+        if book_rules != rules {
+            return Err(BookError::WrongRules {
+                book: book_rules,
+                expected: rules,
+            });
+        }
+
+        let count = reader.u32()?;
+        let squares = width * height;
+        let mut moves = HashMap::new();
+        for entry in 0..count {
+            let (board, to_move, best) =
+                decode_entry(&mut reader, squares)?.ok_or(BookError::InvalidEntry(entry))?;
+            moves.insert((board, to_move), best);
+        }
+
+        Ok(Book {
+            rules,
+            depth,
+            moves,
+        })
+    }
+}
+
+fn encode_entry(board: &[Symbol], to_move: Player, best: usize) -> Vec<u8> {
+    let squares_with = |symbol| {
+        board
+            .iter()
+            .enumerate()
+            .filter(move |(_, square)| **square == symbol)
+            .map(|(index, _)| index)
+    };
+    let crosses: Vec<usize> = squares_with(Symbol::Cross).collect();
+    let naughts: Vec<usize> = squares_with(Symbol::Naught).collect();
+
+    let mut values = vec![crosses.len(), naughts.len()];
+    values.extend(crosses);
+    values.extend(naughts);
+    values.push(best);
+
+    let mut bytes = vec![match to_move {
+        Player::Cross => 0,
+        Player::Naught => 1,
+    }];
+    for value in values {
+        bytes.extend_from_slice(&(value as u16).to_le_bytes());
+    }
+    bytes
+}
+
+/// Reads one position, `None` if it isn't a possible one: pieces off the
+/// board or on top of each other, or a best move onto a taken square.
+fn decode_entry(
+    reader: &mut Reader,
+    squares: usize,
+) -> Result<Option<(Vec<Symbol>, Player, usize)>, BookError> {
+    let to_move = match reader.u8()? {
+        0 => Some(Player::Cross),
+        1 => Some(Player::Naught),
+        _ => None,
+    };
+    let crosses = reader.u16()?;
+    let naughts = reader.u16()?;
+
+    let mut board = vec![Symbol::Empty; squares];
+    let mut valid = to_move.is_some();
+    for symbol in (0..crosses)
+        .map(|_| Symbol::Cross)
+        .chain((0..naughts).map(|_| Symbol::Naught))
+    {
+        match board.get_mut(reader.u16()?) {
+            Some(square) if *square == Symbol::Empty => *square = symbol,
+            _ => valid = false,
+        }
+    }
+
+    let best = reader.u16()?;
+    valid &= board.get(best) == Some(&Symbol::Empty);
+
+    Ok(to_move
+        .filter(|_| valid)
+        .map(|to_move| (board, to_move, best)))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], BookError> {
+        if self.bytes.len() < N {
+            return Err(BookError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(N);
+        self.bytes = rest;
+
+        let mut array = [0; N];
+        array.copy_from_slice(taken);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<usize, BookError> {
+        Ok(usize::from(self.take::<1>()?[0]))
+    }
+
+    fn u16(&mut self) -> Result<usize, BookError> {
+        Ok(usize::from(u16::from_le_bytes(self.take()?)))
+    }
+
+    fn u32(&mut self) -> Result<u32, BookError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum BookError {
+    NotABook,
+    UnsupportedVersion(u8),
+    Truncated,
+    ChecksumMismatch,
+    InvalidRules(RulesError),
+    /// The book is fine but was made for a different board.
+    WrongRules {
+        book: Rules,
+        expected: Rules,
+    },
+    /// The position at this number in the book can't happen.
+    InvalidEntry(u32),
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let describe = |rules: &Rules| {
+            format!(
+                "{}x{} with {} in a row",
+                rules.width(),
+                rules.height(),
+                rules.win_length()
+            )
+        };
+
+        match self {
+            BookError::NotABook => write!(f, "not an opening book"),
+            BookError::UnsupportedVersion(version) => write!(
+                f,
+                "the book is format version {}, only version {} can be read",
+                version, FORMAT_VERSION
+            ),
+            BookError::Truncated => write!(f, "the book is cut short"),
+            BookError::ChecksumMismatch => {
+                write!(f, "the book is damaged, its checksum doesn't match")
+            }
+            BookError::InvalidRules(error) => {
+                write!(f, "the book's rules are impossible: {}", error)
+            }
+            BookError::WrongRules { book, expected } => write!(
+                f,
+                "the book is for {} but the game is {}",
+                describe(book),
+                describe(expected)
+            ),
+            BookError::InvalidEntry(entry) => {
+                write!(f, "position {} in the book can't happen", entry)
+            }
+        }
+    }
+}
+
+impl Error for BookError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BookError::InvalidRules(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Solver;
+    use crate::computer::{Computer, Difficulty};
+    use crate::testing::play_moves;
+    use rstest::rstest;
+
+    #[rstest]
+    // The empty board with either player to move.
+    #[case(0, 2)]
+    // Then a corner, an edge or the centre, again for either player.
+    #[case(1, 8)]
+    fn positions_are_stored_once_per_symmetry(#[case] depth: usize, #[case] expected: usize) {
+        assert_eq!(Book::generate(Rules::classic(), depth).len(), expected);
+    }
+
+    #[test]
+    fn book_moves_are_as_good_as_perfect_play() {
+        let book = Book::generate(Rules::classic(), 2);
+        let mut solver = Solver::new();
+
+        for moves in [vec![], vec![0], vec![8], vec![1], vec![4, 6], vec![7, 4]] {
+            let game = play_moves(&moves);
+            let best = book.best_move(&game).unwrap();
+
+            assert_eq!(game.board()[best], Symbol::Empty);
+            assert_eq!(
+                solver.evaluate_move(&game, best),
+                Some(solver.evaluate(&game)),
+                "after {:?}",
+                moves
+            );
+        }
+    }
+
+    #[test]
+    fn positions_past_the_depth_or_for_other_rules_are_not_in_the_book() {
+        let book = Book::generate(Rules::classic(), 1);
+
+        assert_eq!(book.best_move(&play_moves(&[4, 0])), None);
+        assert_eq!(book.best_move(&Game::with_rules(4, 4, 3).unwrap()), None);
+    }
+
+    #[test]
+    fn books_round_trip_through_bytes() {
+        let book = Book::generate(Rules::classic(), 2);
+        let bytes = book.to_bytes();
+
+        assert_eq!(&bytes[..5], b"TTTB\x01");
+        assert_eq!(Book::from_bytes(&bytes, Rules::classic()), Ok(book.clone()));
+        assert_eq!(Book::generate(Rules::classic(), 2).to_bytes(), bytes);
+    }
+
+    #[test]
+    fn books_for_other_rules_are_rejected() {
+        let bytes = Book::generate(Rules::classic(), 0).to_bytes();
+        let rules = Rules::new(4, 4, 3).unwrap();
+
+        assert_eq!(
+            Book::from_bytes(&bytes, rules),
+            Err(BookError::WrongRules {
+                book: Rules::classic(),
+                expected: rules
+            })
+        );
+    }
+
+    #[test]
+    fn damaged_books_are_rejected() {
+        let bytes = Book::generate(Rules::classic(), 1).to_bytes();
+        let read = |bytes: &[u8]| Book::from_bytes(bytes, Rules::classic());
+
+        let mut flipped = bytes.clone();
+        flipped[20] ^= 1;
+        assert_eq!(read(&flipped), Err(BookError::ChecksumMismatch));
+
+        let mut newer = bytes.clone();
+        newer[4] = FORMAT_VERSION + 1;
+        assert_eq!(read(&newer), Err(BookError::UnsupportedVersion(2)));
+
+        assert_eq!(read(b"3x3:3 X../.../... O - -"), Err(BookError::NotABook));
+        assert_eq!(read(b"TTTB"), Err(BookError::Truncated));
+        assert_eq!(
+            read(&bytes[..bytes.len() - 1]),
+            Err(BookError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn impossible_positions_are_rejected_even_with_a_good_checksum() {
+        let mut book = Book::generate(Rules::classic(), 0);
+        book.moves
+            .insert((vec![Symbol::Empty; 9], Player::Cross), 9);
+        let bytes = book.to_bytes();
+
+        assert_eq!(
+            Book::from_bytes(&bytes, Rules::classic()),
+            Err(BookError::InvalidEntry(0))
+        );
+    }
+
+    #[test]
+    fn the_hard_computer_plays_from_the_book() {
+        // Every opening draws, so the search would take the first square.
+        let mut book = Book::generate(Rules::classic(), 0);
+        book.moves
+            .insert((vec![Symbol::Empty; 9], Player::Cross), 4);
+        let game = Game::start();
+
+        assert_eq!(computer::best_move(&game), Some(0));
+        let mut computer = Computer::with_seed(Difficulty::Hard, 0).with_book(book);
+        assert_eq!(computer.choose_move(&game), Some(4));
+    }
+}
@@ -0,0 +1,471 @@
+use crate::book::Book;
+use crate::game::{GameStatus, Player};
+use crate::variant::Variant;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::str::FromStr;
+
+// Any score larger than the deepest possible game, so quicker wins score higher.
+const WIN_SCORE: i32 = 1_000_000;
+
+// Roughly how many positions a search may visit. A 3x3 board is always
+// searched to the end, larger boards only look a few moves ahead.
+const SEARCH_BUDGET: usize = 1_000_000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Difficulty, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "easy" | "1" => Ok(Difficulty::Easy),
+            "medium" | "2" => Ok(Difficulty::Medium),
+            "hard" | "3" => Ok(Difficulty::Hard),
+            _ => Err(format!(
+                "Unknown difficulty '{}', expected easy, medium or hard",
+                value
+            )),
+        }
+    }
+}
+
+pub struct Computer {
+    difficulty: Difficulty,
+    rng: StdRng,
+    book: Option<Book>,
+}
+
+impl Computer {
+    pub fn new(difficulty: Difficulty) -> Computer {
+        Computer {
+            difficulty,
+            rng: StdRng::from_entropy(),
+            book: None,
+        }
+    }
+
+    /// Creates a computer whose random choices are repeatable for a given seed.
+    pub fn with_seed(difficulty: Difficulty, seed: u64) -> Computer {
+        Computer {
+            difficulty,
+            rng: StdRng::seed_from_u64(seed),
+            book: None,
+        }
+    }
+
+    /// Plays from `book` where it has the position, only searching once the
+    /// game has left it. Only the hard computer uses the book.
+    pub fn with_book(self, book: Book) -> Computer {
+        Computer {
+            book: Some(book),
+            ..self
+        }
+    }
+
+    /// How the computer appears on the leaderboard, e.g. `Computer (hard)`.
+    pub fn name(&self) -> String {
+        let difficulty = match self.difficulty {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        };
+        format!("Computer ({})", difficulty)
+    }
+
+    /// Picks a move for the current player, or `None` once the game is over.
+    pub fn choose_move<V: Variant>(&mut self, game: &V) -> Option<V::Move> {
+        if !matches!(game.status(), GameStatus::InProgress { .. }) {
+            return None;
+        }
+
+        match self.difficulty {
+            Difficulty::Easy => self.random_move(&game.legal_moves()),
+            Difficulty::Medium => self.cautious_move(game),
+            Difficulty::Hard => self
+                .book
+                .as_ref()
+                .and_then(|book| game.book_move(book))
+                .or_else(|| best_move(game)),
+        }
+    }
+
+    /// Takes a win if one is on offer, otherwise plays randomly among the
+    /// moves that don't hand the opponent a win on their next turn.
+    fn cautious_move<V: Variant>(&mut self, game: &V) -> Option<V::Move> {
+        let moves = game.legal_moves();
+
+        if let Some(mv) = moves.iter().find(|mv| wins_immediately(game, **mv)) {
+            return Some(*mv);
+        }
+
+        let safe_moves: Vec<V::Move> = moves
+            .iter()
+            .copied()
+            .filter(|mv| {
+                let mut next = game.clone();
+                next.apply(*mv).is_ok()
+                    && !loses(&next, game.current_player())
+                    && !next
+                        .legal_moves()
+                        .iter()
+                        .any(|reply| wins_immediately(&next, *reply))
+            })
+            .collect();
+
+        if safe_moves.is_empty() {
+            return self.random_move(&moves);
+        }
+
+        self.random_move(&safe_moves)
+    }
+
+    fn random_move<M: Copy>(&mut self, moves: &[M]) -> Option<M> {
+        moves.choose(&mut self.rng).copied()
+    }
+}
+
+fn wins_immediately<V: Variant>(game: &V, mv: V::Move) -> bool {
+    let mut next = game.clone();
+    next.apply(mv).is_ok()
+        && matches!(next.status(), GameStatus::Won { by, .. } if by == game.current_player())
+}
+
+/// Whether `player` has lost, which in some variants can happen on their own
+/// move.
+fn loses<V: Variant>(game: &V, player: Player) -> bool {
+    matches!(game.status(), GameStatus::Won { by, .. } if by != player)
+}
+
+/// Picks the move for the current player using minimax with alpha-beta
+/// pruning. Returns `None` when there is nothing left to play.
+pub fn best_move<V: Variant>(game: &V) -> Option<V::Move> {
+    search(game, SEARCH_BUDGET)
+}
+
+/// `best_move`, looking as far ahead as visiting about `budget` positions
+/// allows.
+pub(crate) fn search<V: Variant>(game: &V, budget: usize) -> Option<V::Move> {
+    let moves = game.legal_moves();
+    let max_depth = search_depth(moves.len(), budget);
+
+    let mut best_move = None;
+    let mut alpha = -WIN_SCORE - 1;
+    let beta = WIN_SCORE + 1;
+
+    for mv in moves {
+        let mut next = game.clone();
+        if next.apply(mv).is_err() {
+            continue;
+        }
+
+        let score = -negamax(&next, 1, max_depth, -beta, -alpha);
+        if best_move.is_none() || score > alpha {
+            alpha = score;
+            best_move = Some(mv);
+        }
+    }
+
+    best_move
+}
+
+/// How many moves ahead the search can look with `moves` squares left
+/// without visiting more than `budget` positions.
+fn search_depth(moves: usize, budget: usize) -> i32 {
+    let mut positions: usize = 1;
+    let mut depth = 0;
+
+    for remaining in (1..=moves).rev() {
+        positions = positions.saturating_mul(remaining);
+        if positions > budget {
+            break;
+        }
+        depth += 1;
+    }
+
+    depth.max(1)
+}
+
+/// Scores a position from the point of view of the player about to move.
+/// Positions at `max_depth` that are still undecided score as even.
+fn negamax<V: Variant>(game: &V, depth: i32, max_depth: i32, mut alpha: i32, beta: i32) -> i32 {
+    match game.status() {
+        // Usually the winner made the previous move, but not in every
+        // variant.
+        GameStatus::Won { by, .. } if by == game.current_player() => return WIN_SCORE - depth,
+        GameStatus::Won { .. } => return depth - WIN_SCORE,
+        GameStatus::Draw => return 0,
+        GameStatus::InProgress { .. } => {}
+    }
+
+    let moves = game.legal_moves();
+    if moves.is_empty() || depth >= max_depth {
+        return 0;
+    }
+
+    let mut best_score = -WIN_SCORE - 1;
+    for mv in moves {
+        let mut next = game.clone();
+        if next.apply(mv).is_err() {
+            continue;
+        }
+
+        let score = -negamax(&next, depth + 1, max_depth, -beta, -alpha);
+        best_score = best_score.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    best_score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, Rules};
+    use crate::testing::{play_moves, play_on};
+    use crate::variant::{Misere, NumberMove, Numerical, Wild, WildMove};
+    use rstest::rstest;
+
+    #[rstest]
+    // X to play, X can complete the top row.
+    #[case(vec![0, 3, 1, 4], 2)]
+    // O to play, O can complete the middle column.
+    #[case(vec![0, 1, 2, 4, 8], 7)]
+    fn takes_a_winning_move(#[case] moves: Vec<usize>, #[case] expected: usize) {
+        let game = play_moves(&moves);
+
+        assert_eq!(best_move(&game), Some(expected));
+    }
+
+    #[rstest]
+    // O to play, X threatens the top row.
+    #[case(vec![0, 4, 1], 2)]
+    // X to play, O threatens the left column.
+    #[case(vec![4, 0, 8, 3], 6)]
+    fn blocks_an_immediate_threat(#[case] moves: Vec<usize>, #[case] expected: usize) {
+        let game = play_moves(&moves);
+
+        assert_eq!(best_move(&game), Some(expected));
+    }
+
+    #[test]
+    fn no_move_is_chosen_when_the_game_is_over() {
+        let game = play_moves(&[0, 3, 1, 4, 2]);
+
+        assert_eq!(best_move(&game), None);
+    }
+
+    #[test]
+    fn perfect_play_against_itself_is_a_draw() {
+        let mut game = Game::start();
+        while let Some(index) = best_move(&game) {
+            game.play(index).ok();
+        }
+
+        assert_eq!(game.status(), GameStatus::Draw);
+    }
+
+    // Tries every possible reply the opponent could make and checks the
+    // computer never ends up losing.
+    fn assert_never_loses(game: &Game, computer: Player) {
+        match game.status() {
+            GameStatus::InProgress { next } if next == computer => {
+                let mut next = game.clone();
+                next.play(best_move(game).unwrap()).ok();
+                assert_never_loses(&next, computer);
+            }
+            GameStatus::InProgress { .. } => {
+                for index in game.available_moves() {
+                    let mut next = game.clone();
+                    next.play(index).ok();
+                    assert_never_loses(&next, computer);
+                }
+            }
+            GameStatus::Won { by, .. } => {
+                assert_eq!(by, computer, "Computer lost with board {:?}", game.board())
+            }
+            GameStatus::Draw => {}
+        }
+    }
+
+    #[rstest]
+    #[case(Player::Cross)]
+    #[case(Player::Naught)]
+    fn never_loses_against_any_opponent(#[case] computer: Player) {
+        assert_never_loses(&Game::start(), computer);
+    }
+
+    #[test]
+    fn difficulty_is_parsed_from_names_and_menu_numbers() {
+        assert_eq!("easy".parse(), Ok(Difficulty::Easy));
+        assert_eq!("Medium".parse(), Ok(Difficulty::Medium));
+        assert_eq!("3".parse(), Ok(Difficulty::Hard));
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+
+    fn play_out(computer: &mut Computer) -> Vec<usize> {
+        let mut game = Game::start();
+        let mut moves = Vec::new();
+        while let Some(index) = computer.choose_move(&game) {
+            game.play(index).ok();
+            moves.push(index);
+        }
+        moves
+    }
+
+    #[rstest]
+    #[case(Difficulty::Easy)]
+    #[case(Difficulty::Medium)]
+    fn same_seed_plays_the_same_moves(#[case] difficulty: Difficulty) {
+        let first = play_out(&mut Computer::with_seed(difficulty, 42));
+        let second = play_out(&mut Computer::with_seed(difficulty, 42));
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn easy_only_plays_legal_moves() {
+        for seed in 0..20 {
+            let mut computer = Computer::with_seed(Difficulty::Easy, seed);
+            let mut game = Game::start();
+            while let Some(index) = computer.choose_move(&game) {
+                assert!(game.play(index).is_ok(), "{} should be playable", index);
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0, 3, 1, 4], 2)]
+    #[case(vec![0, 1, 2, 4, 8], 7)]
+    fn medium_takes_a_winning_move(#[case] moves: Vec<usize>, #[case] expected: usize) {
+        let game = play_moves(&moves);
+
+        for seed in 0..10 {
+            let mut computer = Computer::with_seed(Difficulty::Medium, seed);
+            assert_eq!(computer.choose_move(&game), Some(expected));
+        }
+    }
+
+    #[rstest]
+    #[case(vec![0, 4, 1], 2)]
+    #[case(vec![4, 0, 8, 3], 6)]
+    fn medium_blocks_an_immediate_threat(#[case] moves: Vec<usize>, #[case] expected: usize) {
+        let game = play_moves(&moves);
+
+        for seed in 0..10 {
+            let mut computer = Computer::with_seed(Difficulty::Medium, seed);
+            assert_eq!(computer.choose_move(&game), Some(expected));
+        }
+    }
+
+    #[test]
+    fn hard_plays_the_best_move() {
+        let game = play_moves(&[0, 4, 1]);
+        let mut computer = Computer::with_seed(Difficulty::Hard, 0);
+
+        assert_eq!(computer.choose_move(&game), best_move(&game));
+    }
+
+    #[test]
+    fn small_boards_are_searched_to_the_end() {
+        assert_eq!(search_depth(9, SEARCH_BUDGET), 9);
+    }
+
+    #[test]
+    fn large_boards_are_searched_a_few_moves_ahead() {
+        assert_eq!(search_depth(225, SEARCH_BUDGET), 2);
+    }
+
+    #[test]
+    fn hard_takes_a_win_on_a_large_board() {
+        let mut game = Game::with_rules(15, 15, 5).unwrap();
+        play_on(&mut game, &[105, 0, 106, 1, 107, 2, 108, 30]);
+
+        assert_eq!(best_move(&game), Some(109));
+    }
+
+    #[test]
+    fn avoids_completing_a_line_in_misere() {
+        // X has two in the top row, so playing the last square there loses.
+        let mut game = Misere::new(Rules::classic());
+        for index in [0, 4, 1, 8].iter() {
+            game.apply(*index).ok();
+        }
+
+        assert_ne!(best_move(&game), Some(2));
+        for seed in 0..10 {
+            let mut computer = Computer::with_seed(Difficulty::Medium, seed);
+            assert_ne!(computer.choose_move(&game), Some(2));
+        }
+    }
+
+    #[test]
+    fn takes_a_win_with_either_symbol_in_wild() {
+        let mut game = Wild::new(Rules::classic());
+        for (square, symbol) in
+            [(0, Player::Naught), (4, Player::Cross), (1, Player::Naught)].iter()
+        {
+            game.apply(WildMove {
+                square: *square,
+                symbol: *symbol,
+            })
+            .ok();
+        }
+
+        // O to play, and an O in the corner finishes the top row.
+        assert_eq!(
+            best_move(&game),
+            Some(WildMove {
+                square: 2,
+                symbol: Player::Naught
+            })
+        );
+    }
+
+    #[test]
+    fn adds_up_to_15_in_numerical() {
+        let mut game = Numerical::new();
+        for (square, number) in [(0, 1), (4, 2), (1, 9)].iter() {
+            game.apply(NumberMove {
+                square: *square,
+                number: *number,
+            })
+            .ok();
+        }
+
+        // X threatens 1 + 9 + 5 along the top row, but O's 4 under the 2
+        // makes 9 + 2 + 4 down the middle column first.
+        assert_eq!(
+            best_move(&game),
+            Some(NumberMove {
+                square: 7,
+                number: 4
+            })
+        );
+    }
+
+    #[test]
+    fn perfect_play_in_misere_is_a_draw() {
+        let mut game = Misere::new(Rules::classic());
+        while let Some(mv) = best_move(&game) {
+            game.apply(mv).ok();
+        }
+
+        assert_eq!(game.status(), GameStatus::Draw);
+    }
+
+    #[test]
+    fn computers_are_named_after_their_difficulty() {
+        assert_eq!(Computer::new(Difficulty::Easy).name(), "Computer (easy)");
+        assert_eq!(Computer::new(Difficulty::Hard).name(), "Computer (hard)");
+    }
+}
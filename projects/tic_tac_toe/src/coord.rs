@@ -0,0 +1,319 @@
+use crate::game::{Game, PlayError, Rules};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A square given by row and column, both counted from zero. Written as a
+/// column letter followed by a row number, so `A1` is the top left square.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Coord {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Coord {
+    pub fn from_index(index: usize, rules: Rules) -> Coord {
+        Coord {
+            row: index / rules.width(),
+            col: index % rules.width(),
+        }
+    }
+
+    /// The board index of this square, or `None` if it's off the board.
+    pub fn to_index(self, rules: Rules) -> Option<usize> {
+        if self.row >= rules.height() || self.col >= rules.width() {
+            return None;
+        }
+
+        Some(self.row * rules.width() + self.col)
+    }
+}
+
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Widened so the last possible row can still be counted from one.
+        write!(f, "{}{}", column_name(self.col), self.row as u128 + 1)
+    }
+}
+
+/// Letters for a column, continuing `Z`, `AA`, `AB` and so on for wide boards.
+pub fn column_name(col: usize) -> String {
+    let mut name = Vec::new();
+    let mut remaining = Some(col);
+
+    while let Some(col) = remaining {
+        name.push(b'A' + (col % 26) as u8);
+        remaining = (col / 26).checked_sub(1);
+    }
+
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// Parses `B2` style coordinates (column letter, then row number) or a
+/// `row,col` pair such as `2,2`. Both count from one.
+impl FromStr for Coord {
+    type Err = ParseCoordError;
+
+    fn from_str(text: &str) -> Result<Coord, ParseCoordError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(ParseCoordError::Empty);
+        }
+
+        let invalid = || ParseCoordError::Invalid(text.to_string());
+
+        if let Some((row, col)) = text.split_once(',') {
+            let row = parse_number(row.trim()).ok_or_else(invalid)?;
+            let col = parse_number(col.trim()).ok_or_else(invalid)?;
+            return Ok(Coord { row, col });
+        }
+
+        let letters_end = text
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .ok_or_else(invalid)?;
+        let (letters, number) = text.split_at(letters_end);
+        if letters.is_empty() {
+            return Err(invalid());
+        }
+
+        let col = letters
+            .to_ascii_uppercase()
+            .bytes()
+            .try_fold(0usize, |col, letter| {
+                col.checked_mul(26)?
+                    .checked_add((letter - b'A') as usize + 1)
+            })
+            .ok_or_else(invalid)?
+            - 1;
+        let row = parse_number(number).ok_or_else(invalid)?;
+
+        Ok(Coord { row, col })
+    }
+}
+
+/// A square as typed by a player: a plain number is a board index and
+/// anything else is read as a coordinate.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Square {
+    Index(usize),
+    Coord(Coord),
+}
+
+impl Square {
+    pub fn play(self, game: &mut Game) -> Result<(), PlayError> {
+        match self {
+            Square::Index(index) => game.play(index),
+            Square::Coord(coord) => game.play_at(coord.row, coord.col),
+        }
+    }
+
+    /// The board index of this square, or `None` if it's off the board.
+    pub fn to_index(self, rules: Rules) -> Option<usize> {
+        match self {
+            Square::Index(index) if index < rules.squares() => Some(index),
+            Square::Index(_) => None,
+            Square::Coord(coord) => coord.to_index(rules),
+        }
+    }
+}
+
+/// Reads a square as typed by a player, as an index or a coordinate, and
+/// gives its board index.
+pub fn parse_square(text: &str, rules: Rules) -> Result<usize, SquareError> {
+    let square: Square = text.parse().map_err(SquareError::Invalid)?;
+
+    square
+        .to_index(rules)
+        .ok_or_else(|| SquareError::OffBoard(text.trim().to_string()))
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Square::Index(index) => write!(f, "{}", index),
+            Square::Coord(coord) => write!(f, "{}", coord),
+        }
+    }
+}
+
+impl FromStr for Square {
+    type Err = ParseCoordError;
+
+    fn from_str(text: &str) -> Result<Square, ParseCoordError> {
+        match text.trim().parse() {
+            Ok(index) => Ok(Square::Index(index)),
+            Err(_) => text.parse().map(Square::Coord),
+        }
+    }
+}
+
+/// Parses a number counted from one into one counted from zero.
+fn parse_number(text: &str) -> Option<usize> {
+    text.parse::<usize>().ok()?.checked_sub(1)
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ParseCoordError {
+    Empty,
+    Invalid(String),
+}
+
+impl fmt::Display for ParseCoordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseCoordError::Empty => write!(f, "no square was given"),
+            ParseCoordError::Invalid(text) => {
+                write!(f, "'{}' is not a square, expected e.g. B2 or 2,2", text)
+            }
+        }
+    }
+}
+
+impl Error for ParseCoordError {}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum SquareError {
+    Invalid(ParseCoordError),
+    /// The square as it was typed.
+    OffBoard(String),
+}
+
+impl fmt::Display for SquareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SquareError::Invalid(error) => write!(f, "{}", error),
+            SquareError::OffBoard(text) => write!(f, "square {} is off the board", text),
+        }
+    }
+}
+
+impl Error for SquareError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SquareError::Invalid(error) => Some(error),
+            SquareError::OffBoard(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Symbol;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("A1", 0, 0)]
+    #[case("B2", 1, 1)]
+    #[case("c1", 0, 2)]
+    #[case("A3", 2, 0)]
+    #[case("O15", 14, 14)]
+    #[case("AA1", 0, 26)]
+    #[case("2,2", 1, 1)]
+    #[case("1, 3", 0, 2)]
+    #[case(" 3,1 ", 2, 0)]
+    fn coordinates_are_parsed(#[case] text: &str, #[case] row: usize, #[case] col: usize) {
+        assert_eq!(text.parse(), Ok(Coord { row, col }));
+    }
+
+    #[rstest]
+    #[case("B")]
+    #[case("2")]
+    #[case("B0")]
+    #[case("0,1")]
+    #[case("B2x")]
+    #[case("2,")]
+    #[case("a,b")]
+    #[case("-1,2")]
+    #[case("AAAAAAAAAAAAAAAAAAAA1")]
+    fn malformed_coordinates_are_rejected(#[case] text: &str) {
+        assert_eq!(
+            text.parse::<Coord>(),
+            Err(ParseCoordError::Invalid(text.to_string()))
+        );
+    }
+
+    #[test]
+    fn empty_input_is_rejected() {
+        assert_eq!("  ".parse::<Coord>(), Err(ParseCoordError::Empty));
+    }
+
+    #[rstest]
+    #[case(0, "A1")]
+    #[case(4, "B2")]
+    #[case(8, "C3")]
+    #[case(5, "C2")]
+    fn indexes_convert_to_and_from_coordinates(#[case] index: usize, #[case] name: &str) {
+        let coord = Coord::from_index(index, Rules::classic());
+
+        assert_eq!(coord.to_string(), name);
+        assert_eq!(coord.to_index(Rules::classic()), Some(index));
+    }
+
+    #[test]
+    fn coordinates_off_the_board_have_no_index() {
+        let rules = Rules::new(4, 2, 3).unwrap();
+
+        assert_eq!(Coord { row: 1, col: 3 }.to_index(rules), Some(7));
+        assert_eq!(Coord { row: 2, col: 0 }.to_index(rules), None);
+        assert_eq!(Coord { row: 0, col: 4 }.to_index(rules), None);
+    }
+
+    #[rstest]
+    #[case("4", Square::Index(4))]
+    #[case(" 10 ", Square::Index(10))]
+    #[case("B2", Square::Coord(Coord { row: 1, col: 1 }))]
+    #[case("1,3", Square::Coord(Coord { row: 0, col: 2 }))]
+    fn squares_are_indexes_or_coordinates(#[case] text: &str, #[case] square: Square) {
+        assert_eq!(text.parse(), Ok(square));
+    }
+
+    #[test]
+    fn squares_play_on_the_game() {
+        let mut game = Game::start();
+
+        assert!(Square::Index(4).play(&mut game).is_ok());
+        assert!(matches!(
+            Square::Coord(Coord { row: 1, col: 1 }).play(&mut game),
+            Err(PlayError::SquareTaken { .. })
+        ));
+        assert_eq!(game.board()[4], Symbol::Cross);
+    }
+
+    #[rstest]
+    #[case("4", Some(4))]
+    #[case("9", None)]
+    #[case("C3", Some(8))]
+    #[case("D1", None)]
+    #[case("3,1", Some(6))]
+    fn squares_have_an_index_only_on_the_board(#[case] text: &str, #[case] index: Option<usize>) {
+        let square: Square = text.parse().unwrap();
+
+        assert_eq!(square.to_index(Rules::classic()), index);
+    }
+
+    #[test]
+    fn typed_squares_are_read_as_indexes() {
+        assert_eq!(parse_square(" b2 ", Rules::classic()), Ok(4));
+        assert_eq!(
+            parse_square("D1", Rules::classic()),
+            Err(SquareError::OffBoard(String::from("D1")))
+        );
+        assert_eq!(
+            parse_square("", Rules::classic()),
+            Err(SquareError::Invalid(ParseCoordError::Empty))
+        );
+        assert_eq!(parse_square("D1", Rules::new(4, 4, 3).unwrap()), Ok(3));
+    }
+
+    #[rstest]
+    #[case(0, "A")]
+    #[case(25, "Z")]
+    #[case(26, "AA")]
+    #[case(27, "AB")]
+    #[case(52, "BA")]
+    fn columns_are_named_with_letters(#[case] col: usize, #[case] name: &str) {
+        assert_eq!(column_name(col), name);
+    }
+}
@@ -0,0 +1,364 @@
+//! Text rendering for the board. The alternate flag (`{:#}`) turns on ANSI
+//! colours for terminals that support them.
+
+use crate::coord::column_name;
+use crate::game::{Game, GameStatus, Player, Symbol};
+use crate::ultimate::Ultimate;
+use crate::variant::{Misere, Numerical, Variant, Wild};
+use std::fmt;
+
+const RESET: &str = "\x1b[0m";
+const CROSS_COLOUR: &str = "\x1b[1;31m";
+const NAUGHT_COLOUR: &str = "\x1b[1;34m";
+const HIGHLIGHT: &str = "\x1b[42m";
+const INVERSE: &str = "\x1b[7m";
+
+/// The letter for `symbol` and the colour code it's drawn in.
+fn letter_and_colour(symbol: Symbol) -> (&'static str, &'static str) {
+    match symbol {
+        Symbol::Cross => ("X", CROSS_COLOUR),
+        Symbol::Naught => ("O", NAUGHT_COLOUR),
+        Symbol::Empty => (" ", ""),
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (text, colour) = letter_and_colour(*self);
+
+        if f.alternate() && !colour.is_empty() {
+            write!(f, "{}{}{}", colour, text, RESET)
+        } else {
+            write!(f, "{}", text)
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&Symbol::from(*self), f)
+    }
+}
+
+/// Draws the board as a grid with column letters along the top and row
+/// numbers down the side. The winning line, if there is one, is shown in
+/// brackets, or highlighted when colours are on.
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in board_lines(self, f.alternate(), None) {
+            writeln!(f, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The lines `Display` draws for a board, optionally marking the square
+/// under a cursor with inverse video, or `<` and `>` without colours.
+pub fn board_lines(game: &Game, colour: bool, cursor: Option<usize>) -> Vec<String> {
+    let rules = game.rules();
+    let winning_line = match game.status() {
+        GameStatus::Won { line, .. } => line,
+        _ => Vec::new(),
+    };
+
+    let label_width = rules.height().to_string().len();
+    let margin = " ".repeat(label_width + 1);
+    let mut lines = Vec::new();
+
+    let columns: Vec<String> = (0..rules.width())
+        .map(|col| format!("{:^3}", column_name(col)))
+        .collect();
+    lines.push(format!("{}{}", margin, columns.join(" ").trim_end()));
+
+    let separator = vec!["---"; rules.width()].join("+");
+
+    for (row, squares) in game.board().chunks(rules.width()).enumerate() {
+        if row > 0 {
+            lines.push(format!("{}{}", margin, separator));
+        }
+
+        let cells: Vec<String> = squares
+            .iter()
+            .enumerate()
+            .map(|(col, square)| {
+                let index = row * rules.width() + col;
+                let highlighted = winning_line.contains(&index);
+                let under_cursor = cursor == Some(index);
+                // The background goes after the symbol's colour so that one
+                // reset at the end of the cell clears both.
+                let (letter, symbol_colour) = letter_and_colour(*square);
+                match (colour, under_cursor, highlighted) {
+                    (true, true, _) => {
+                        format!("{}{} {} {}", symbol_colour, INVERSE, letter, RESET)
+                    }
+                    (true, false, true) => {
+                        format!("{}{} {} {}", symbol_colour, HIGHLIGHT, letter, RESET)
+                    }
+                    (true, false, false) => format!(" {:#} ", square),
+                    (false, true, _) => format!("<{}>", square),
+                    (false, false, true) => format!("[{}]", square),
+                    (false, false, false) => format!(" {} ", square),
+                }
+            })
+            .collect();
+
+        let line = format!("{:>2$} {}", row + 1, cells.join("|"), label_width);
+        lines.push(line.trim_end().to_string());
+    }
+
+    lines
+}
+
+impl fmt::Display for Misere {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.game(), f)
+    }
+}
+
+impl fmt::Display for Wild {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self.game(), f)
+    }
+}
+
+/// Draws the numbers in the same grid as `Game`, coloured by the player who
+/// owns them, followed by the numbers each player has left.
+impl fmt::Display for Numerical {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let winning_line = match self.status() {
+            GameStatus::Won { line, .. } => line,
+            _ => Vec::new(),
+        };
+
+        writeln!(f, "   A   B   C")?;
+        for (row, squares) in self.squares().chunks(3).enumerate() {
+            if row > 0 {
+                writeln!(f, "  ---+---+---")?;
+            }
+
+            let cells: Vec<String> = squares
+                .iter()
+                .enumerate()
+                .map(|(col, square)| {
+                    let text = match square {
+                        Some(number) if f.alternate() => {
+                            let colour = if number % 2 == 1 {
+                                CROSS_COLOUR
+                            } else {
+                                NAUGHT_COLOUR
+                            };
+                            format!("{}{}{}", colour, number, RESET)
+                        }
+                        Some(number) => number.to_string(),
+                        None => String::from(" "),
+                    };
+                    if winning_line.contains(&(row * 3 + col)) {
+                        format!("[{}]", text)
+                    } else {
+                        format!(" {} ", text)
+                    }
+                })
+                .collect();
+            writeln!(
+                f,
+                "{}",
+                format!("{} {}", row + 1, cells.join("|")).trim_end()
+            )?;
+        }
+
+        let left = |player: Player| {
+            let numbers: Vec<String> = self
+                .numbers_left(player)
+                .iter()
+                .map(u8::to_string)
+                .collect();
+            numbers.join(" ")
+        };
+        writeln!(
+            f,
+            "Numbers left: X {}, O {}",
+            left(Player::Cross),
+            left(Player::Naught)
+        )
+    }
+}
+
+/// Draws the nine small boards in a grid, with `.` for empty squares and the
+/// big board's column letters and row numbers around the edge.
+impl fmt::Display for Ultimate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    A       B       C")?;
+
+        for big_row in 0..3 {
+            if big_row > 0 {
+                writeln!(f, "  ------+-------+------")?;
+            }
+
+            for small_row in 0..3 {
+                let label = if small_row == 1 {
+                    (big_row + 1).to_string()
+                } else {
+                    String::from(" ")
+                };
+
+                let blocks: Vec<String> = self.boards()[big_row * 3..big_row * 3 + 3]
+                    .iter()
+                    .map(|board| {
+                        let squares: Vec<String> = board.board()[small_row * 3..small_row * 3 + 3]
+                            .iter()
+                            .map(|square| match (square, f.alternate()) {
+                                (Symbol::Empty, _) => String::from("."),
+                                (square, true) => format!("{:#}", square),
+                                (square, false) => square.to_string(),
+                            })
+                            .collect();
+                        squares.join(" ")
+                    })
+                    .collect();
+
+                writeln!(f, "{} {}", label, blocks.join(" | "))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::play_moves;
+    use crate::variant::NumberMove;
+
+    #[test]
+    fn symbols_are_shown_as_letters() {
+        assert_eq!(Symbol::Cross.to_string(), "X");
+        assert_eq!(Symbol::Naught.to_string(), "O");
+        assert_eq!(Symbol::Empty.to_string(), " ");
+    }
+
+    #[test]
+    fn coloured_symbols_use_ansi_codes() {
+        assert_eq!(format!("{:#}", Symbol::Cross), "\x1b[1;31mX\x1b[0m");
+        assert_eq!(format!("{:#}", Symbol::Naught), "\x1b[1;34mO\x1b[0m");
+        assert_eq!(format!("{:#}", Symbol::Empty), " ");
+    }
+
+    #[test]
+    fn board_is_drawn_with_labels_and_separators() {
+        let game = play_moves(&[0, 4, 8]);
+
+        assert_eq!(
+            game.to_string(),
+            concat!(
+                "   A   B   C\n",
+                "1  X |   |\n",
+                "  ---+---+---\n",
+                "2    | O |\n",
+                "  ---+---+---\n",
+                "3    |   | X\n",
+            )
+        );
+    }
+
+    #[test]
+    fn winning_line_is_bracketed() {
+        let game = play_moves(&[0, 3, 1, 4, 2]);
+
+        assert_eq!(
+            game.to_string(),
+            concat!(
+                "   A   B   C\n",
+                "1 [X]|[X]|[X]\n",
+                "  ---+---+---\n",
+                "2  O | O |\n",
+                "  ---+---+---\n",
+                "3    |   |\n",
+            )
+        );
+    }
+
+    #[test]
+    fn coloured_winning_line_is_highlighted() {
+        let game = play_moves(&[0, 3, 1, 4, 2]);
+        let text = format!("{:#}", game);
+
+        assert_eq!(
+            board_lines(&game, true, None)[1],
+            "1 \x1b[1;31m\x1b[42m X \x1b[0m|\x1b[1;31m\x1b[42m X \x1b[0m|\x1b[1;31m\x1b[42m X \x1b[0m"
+        );
+        assert!(text.contains(" \x1b[1;34mO\x1b[0m "));
+    }
+
+    #[test]
+    fn row_labels_line_up_on_tall_boards() {
+        let game = Game::with_rules(2, 10, 2).unwrap();
+        let lines: Vec<String> = game.to_string().lines().map(String::from).collect();
+
+        assert_eq!(lines[0], "    A   B");
+        assert_eq!(lines[1], " 1    |");
+        assert_eq!(lines[19], "10    |");
+    }
+
+    #[test]
+    fn ultimate_boards_are_drawn_in_a_grid() {
+        let mut game = Ultimate::start();
+        game.play(0, 4).unwrap();
+        game.play(4, 8).unwrap();
+
+        assert_eq!(
+            game.to_string(),
+            concat!(
+                "    A       B       C\n",
+                "  . . . | . . . | . . .\n",
+                "1 . X . | . . . | . . .\n",
+                "  . . . | . . . | . . .\n",
+                "  ------+-------+------\n",
+                "  . . . | . . . | . . .\n",
+                "2 . . . | . . . | . . .\n",
+                "  . . . | . . O | . . .\n",
+                "  ------+-------+------\n",
+                "  . . . | . . . | . . .\n",
+                "3 . . . | . . . | . . .\n",
+                "  . . . | . . . | . . .\n",
+            )
+        );
+    }
+
+    #[test]
+    fn numerical_boards_show_numbers_and_what_is_left() {
+        let mut game = Numerical::new();
+        game.apply(NumberMove {
+            square: 4,
+            number: 5,
+        })
+        .unwrap();
+
+        assert_eq!(
+            game.to_string(),
+            concat!(
+                "   A   B   C\n",
+                "1    |   |\n",
+                "  ---+---+---\n",
+                "2    | 5 |\n",
+                "  ---+---+---\n",
+                "3    |   |\n",
+                "Numbers left: X 1 3 7 9, O 2 4 6 8\n",
+            )
+        );
+    }
+
+    #[test]
+    fn cursor_is_marked() {
+        let game = play_moves(&[4]);
+
+        assert_eq!(board_lines(&game, false, Some(4))[3], "2    |<X>|");
+        let coloured = &board_lines(&game, true, Some(4))[3];
+        assert_eq!(coloured, "2    |\x1b[1;31m\x1b[7m X \x1b[0m|");
+        assert_eq!(
+            board_lines(&game, true, Some(0))[1],
+            "1 \x1b[7m   \x1b[0m|   |"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Everything that can stop the binary early, with the exit code for each.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use tic_tac_toe::book::BookError;
+use tic_tac_toe::game::RulesError;
+
+#[derive(Debug)]
+pub enum CliError {
+    /// The command line asked for something that can't be done.
+    Usage(String),
+    Rules(RulesError),
+    /// A game or position given to a subcommand couldn't be read.
+    InvalidGame {
+        description: String,
+        source: Box<dyn Error>,
+    },
+    /// The opening book is damaged or for other rules.
+    InvalidBook {
+        path: String,
+        source: BookError,
+    },
+    /// Reading a file, the terminal or the network failed part way.
+    Io {
+        action: String,
+        source: io::Error,
+    },
+}
+
+impl CliError {
+    /// 2 for anything wrong with what was asked for, 1 for failures that
+    /// happened while running.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Io { .. } => 1,
+            _ => 2,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}", message),
+            CliError::Rules(error) => write!(f, "Can't set up the board, {}", error),
+            CliError::InvalidGame {
+                description,
+                source,
+            } => write!(f, "{}: {}", description, source),
+            CliError::InvalidBook { path, source } => {
+                write!(f, "{} can't be used as an opening book: {}", path, source)
+            }
+            CliError::Io { action, source } => write!(f, "Couldn't {}: {}", action, source),
+        }
+    }
+}
+
+impl Error for CliError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CliError::Usage(_) => None,
+            CliError::Rules(error) => Some(error),
+            CliError::InvalidGame { source, .. } => Some(source.as_ref()),
+            CliError::InvalidBook { source, .. } => Some(source),
+            CliError::Io { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> CliError {
+        CliError::Usage(message)
+    }
+}
+
+impl From<RulesError> for CliError {
+    fn from(error: RulesError) -> CliError {
+        CliError::Rules(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tic_tac_toe::notation::{self, NotationError};
+
+    #[test]
+    fn problems_with_the_request_exit_with_2() {
+        assert_eq!(CliError::Usage(String::from("bad")).exit_code(), 2);
+        assert_eq!(CliError::Rules(RulesError::EmptyBoard).exit_code(), 2);
+    }
+
+    #[test]
+    fn io_failures_exit_with_1() {
+        let error = CliError::Io {
+            action: String::from("read game.txt"),
+            source: io::Error::new(io::ErrorKind::NotFound, "not found"),
+        };
+
+        assert_eq!(error.exit_code(), 1);
+        assert_eq!(error.to_string(), "Couldn't read game.txt: not found");
+    }
+
+    #[test]
+    fn the_underlying_error_is_kept_as_the_source() {
+        let notation_error = notation::parse("3x3:3").unwrap_err();
+        let error = CliError::InvalidGame {
+            description: String::from("'3x3:3' is not a valid game"),
+            source: Box::new(notation_error.clone()),
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "'3x3:3' is not a valid game: missing the board"
+        );
+        assert_eq!(
+            error
+                .source()
+                .and_then(|source| source.downcast_ref::<NotationError>()),
+            Some(&notation_error)
+        );
+    }
+}
@@ -1,114 +1,638 @@
+use crate::bitboard::{Bitboard, WinLines};
+use crate::coord::{Coord, Square};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
 use Symbol::*;
 
+// Right, down, down-right and down-left. Every line on the board runs along
+// one of these, so checking them from each square finds every line.
+pub(crate) const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+/// The most squares a board can have, so a mistyped size is rejected rather
+/// than using up all the memory.
+pub const MAX_SQUARES: usize = 10_000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rules {
+    width: usize,
+    height: usize,
+    win_length: usize,
+}
+
+impl Rules {
+    pub fn new(width: usize, height: usize, win_length: usize) -> Result<Rules, RulesError> {
+        if width == 0 || height == 0 {
+            return Err(RulesError::EmptyBoard);
+        }
+
+        if win_length == 0 || win_length > width.max(height) {
+            return Err(RulesError::WinLengthDoesNotFit);
+        }
+
+        match width.checked_mul(height) {
+            Some(squares) if squares <= MAX_SQUARES => {}
+            _ => return Err(RulesError::TooManySquares),
+        }
+
+        Ok(Rules {
+            width,
+            height,
+            win_length,
+        })
+    }
+
+    /// The standard 3x3 board with three in a row to win.
+    pub fn classic() -> Rules {
+        Rules {
+            width: 3,
+            height: 3,
+            win_length: 3,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn win_length(&self) -> usize {
+        self.win_length
+    }
+
+    pub fn squares(&self) -> usize {
+        self.width * self.height
+    }
+}
+
+/// The board is kept both as a list of squares, for `board`, and as a
+/// bitboard per player, which makes checking for a win quick enough for the
+/// computer to search deeply.
+#[derive(Clone, PartialEq, Debug)]
 pub struct Game {
+    rules: Rules,
     board: Vec<Symbol>,
-    current_player: Symbol,
+    crosses: Bitboard,
+    naughts: Bitboard,
+    lines: Arc<WinLines>,
+    winner: Option<Player>,
+    current_player: Player,
+    history: Vec<usize>,
+    undone: Vec<usize>,
 }
 
 impl Game {
     pub fn start() -> Game {
+        Game::with(Rules::classic())
+    }
+
+    /// Starts an m,n,k game: a `width` by `height` board where `win_length`
+    /// in a row wins, e.g. `Game::with_rules(15, 15, 5)` for gomoku.
+    pub fn with_rules(width: usize, height: usize, win_length: usize) -> Result<Game, RulesError> {
+        Ok(Game::with(Rules::new(width, height, win_length)?))
+    }
+
+    /// Starts an empty game where `first` makes the opening move.
+    pub fn starting_with(rules: Rules, first: Player) -> Game {
         Game {
-            board: vec![Empty; 9],
-            current_player: Cross,
+            rules,
+            board: vec![Empty; rules.squares()],
+            crosses: Bitboard::new(rules.squares()),
+            naughts: Bitboard::new(rules.squares()),
+            lines: Arc::new(WinLines::new(rules)),
+            winner: None,
+            current_player: first,
+            history: Vec::new(),
+            undone: Vec::new(),
         }
     }
 
+    fn with(rules: Rules) -> Game {
+        Game::starting_with(rules, Player::Cross)
+    }
+
+    /// Sets up a game from a position part way through, with the player to
+    /// move worked out from the pieces on the board. Cross is taken to have
+    /// moved first, so the position is rejected if it couldn't have come from
+    /// a real game.
+    pub fn from_board(rules: Rules, board: Vec<Symbol>) -> Result<Game, PositionError> {
+        Game::from_board_starting_with(rules, board, Player::Cross)
+    }
+
+    /// Like `from_board`, for a game where `first` made the opening move.
+    pub fn from_board_starting_with(
+        rules: Rules,
+        board: Vec<Symbol>,
+        first: Player,
+    ) -> Result<Game, PositionError> {
+        if board.len() != rules.squares() {
+            return Err(PositionError::WrongSize {
+                expected: rules.squares(),
+                found: board.len(),
+            });
+        }
+
+        let crosses = board.iter().filter(|square| **square == Cross).count();
+        let naughts = board.iter().filter(|square| **square == Naught).count();
+        let (firsts, seconds, second) = match first {
+            Player::Cross => (crosses, naughts, Player::Naught),
+            Player::Naught => (naughts, crosses, Player::Cross),
+        };
+        let current_player = match firsts.checked_sub(seconds) {
+            Some(0) => first,
+            Some(1) => second,
+            _ => return Err(PositionError::PieceCounts { crosses, naughts }),
+        };
+
+        let mut game = Game {
+            current_player,
+            ..Game::starting_with(rules, first)
+        };
+        for (index, symbol) in board.into_iter().enumerate() {
+            game.set_square(index, symbol);
+        }
+        game.winner = game.find_winner();
+        game.check_reachable()?;
+
+        Ok(game)
+    }
+
+    fn check_reachable(&self) -> Result<(), PositionError> {
+        let lines = self.find_winning_lines(false);
+        let winner = match lines.first() {
+            Some(line) => self.board[line[0]],
+            None => return Ok(()),
+        };
+
+        if lines.iter().any(|line| self.board[line[0]] != winner) {
+            return Err(PositionError::BothPlayersWon);
+        }
+
+        // The winner must have made the last move...
+        if Symbol::from(self.current_player) == winner {
+            return Err(PositionError::PlayedAfterWin);
+        }
+
+        // ...and that move must have completed every winning line at once.
+        let completed_by_one_move = lines[0].iter().any(|index| {
+            let mut before = self.clone();
+            before.set_square(*index, Empty);
+            before.find_winner().is_none()
+        });
+        if !completed_by_one_move {
+            return Err(PositionError::TooManyLines);
+        }
+
+        Ok(())
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    pub fn board(&self) -> &[Symbol] {
+        &self.board
+    }
+
+    pub fn current_player(&self) -> Player {
+        self.current_player
+    }
+
+    pub fn available_moves(&self) -> Vec<usize> {
+        (0..self.board.len())
+            .filter(|index| self.board[*index] == Empty)
+            .collect()
+    }
+
     pub fn play(&mut self, index: usize) -> Result<(), PlayError> {
-        let error = self.check_for_play_error(index);
-        if error.is_some() {
-            return Err(error.unwrap());
+        if let Some(error) = self.check_for_play_error(index) {
+            return Err(error);
+        }
+
+        self.place(index);
+        self.undone.clear();
+
+        Ok(())
+    }
+
+    /// Plays `index` for `player`, whoever's turn it is. For variants where
+    /// turns don't alternate on this board.
+    pub fn play_as(&mut self, player: Player, index: usize) -> Result<(), PlayError> {
+        let current_player = self.current_player;
+        self.current_player = player;
+        let result = self.play(index);
+        if result.is_err() {
+            self.current_player = current_player;
+        }
+
+        result
+    }
+
+    /// Plays the square at `row` and `col`, both counted from zero.
+    pub fn play_at(&mut self, row: usize, col: usize) -> Result<(), PlayError> {
+        match (Coord { row, col }).to_index(self.rules) {
+            Some(index) => self.play(index),
+            None => Err(PlayError::OutOfRange {
+                square: Square::Coord(Coord { row, col }),
+                rules: self.rules,
+            }),
+        }
+    }
+
+    /// The squares played so far, in the order they were played.
+    pub fn history(&self) -> &[usize] {
+        &self.history
+    }
+
+    /// The squares that `redo` would replay, next redo first.
+    pub fn redo_moves(&self) -> impl Iterator<Item = usize> + '_ {
+        self.undone.iter().rev().copied()
+    }
+
+    /// Takes back the last move, returning the square it was played in.
+    pub fn undo(&mut self) -> Option<usize> {
+        let index = self.history.pop()?;
+
+        // Play stops at a win, so taking back any move leaves no winner.
+        self.set_square(index, Empty);
+        self.winner = None;
+        self.toggle_player();
+        self.undone.push(index);
+
+        Some(index)
+    }
+
+    /// Replays the last undone move, returning the square it was played in.
+    /// Any new move with `play` clears the moves available to redo.
+    pub fn redo(&mut self) -> Option<usize> {
+        let index = self.undone.pop()?;
+
+        self.place(index);
+
+        Some(index)
+    }
+
+    /// Undoes moves until it's a human's turn again, so playing `computer`
+    /// takes back its reply as well. Returns false if there was nothing to
+    /// undo.
+    pub fn undo_turn(&mut self, computer: Option<Player>) -> bool {
+        if self.undo().is_none() {
+            return false;
         }
 
-        self.board[index] = self.current_player;
+        while computer == Some(self.current_player) && self.undo().is_some() {}
+
+        true
+    }
+
+    /// Redoes moves until it's a human's turn again. Returns false if there
+    /// was nothing to redo.
+    pub fn redo_turn(&mut self, computer: Option<Player>) -> bool {
+        if self.redo().is_none() {
+            return false;
+        }
+
+        while computer == Some(self.current_player) && self.redo().is_some() {}
+
+        true
+    }
 
+    fn place(&mut self, index: usize) {
+        let player = self.current_player;
+        self.set_square(index, player.into());
+        if self.lines.completed_through(self.pieces(player), index) {
+            self.winner = Some(player);
+        }
+        self.history.push(index);
         self.toggle_player();
+    }
+
+    fn set_square(&mut self, index: usize, symbol: Symbol) {
+        self.crosses.clear(index);
+        self.naughts.clear(index);
+        match symbol {
+            Cross => self.crosses.set(index),
+            Naught => self.naughts.set(index),
+            Empty => {}
+        }
+        self.board[index] = symbol;
+    }
+
+    fn pieces(&self, player: Player) -> &Bitboard {
+        match player {
+            Player::Cross => &self.crosses,
+            Player::Naught => &self.naughts,
+        }
+    }
+
+    /// Checks every line for a winner, for when there's no last move to go
+    /// on.
+    fn find_winner(&self) -> Option<Player> {
+        [Player::Cross, Player::Naught]
+            .iter()
+            .copied()
+            .find(|player| self.lines.any_complete(self.pieces(*player)))
+    }
 
-        return Ok(());
+    fn is_full(&self) -> bool {
+        self.crosses.count() + self.naughts.count() == self.board.len()
     }
 
     fn check_for_play_error(&self, index: usize) -> Option<PlayError> {
-        if index > 8 {
-            return Some(PlayError::OutOfRange);
-        }
-
-        if self.board[index] != Empty {
-            return Some(PlayError::SquareTaken);
-        }
-
-        return None;
-    }
-
-    pub fn get_winner(&self) -> Option<Symbol> {
-        let top_row = self.get_row(0);
-        let middle_row = self.get_row(1);
-        let bottom_row = self.get_row(2);
-        let left_column = &[self.board[0], self.board[3], self.board[6]];
-        let middle_column = &[self.board[1], self.board[4], self.board[7]];
-        let right_column = &[self.board[2], self.board[5], self.board[8]];
-        let diagonal_right = &[self.board[0], self.board[4], self.board[8]];
-        let diagonal_left = &[self.board[2], self.board[4], self.board[6]];
-
-        for triple in [
-            top_row,
-            middle_row,
-            bottom_row,
-            left_column,
-            middle_column,
-            right_column,
-            diagonal_right,
-            diagonal_left,
-        ]
-        .iter()
-        {
-            let winner = all_match(triple);
-            if winner.is_some() {
-                return winner;
-            }
+        if self.winner.is_some() || self.is_full() {
+            return Some(PlayError::GameOver);
+        }
+
+        if index >= self.board.len() {
+            return Some(PlayError::OutOfRange {
+                square: Square::Index(index),
+                rules: self.rules,
+            });
+        }
+
+        if let Some(by) = self.board[index].player() {
+            return Some(PlayError::SquareTaken { index, by });
         }
 
-        return None;
+        None
     }
 
-    fn get_row(&self, row_num: usize) -> &[Symbol] {
-        let start = row_num * 3;
-        &self.board[start..=start + 2]
+    pub fn get_winner(&self) -> Option<Player> {
+        self.winner
     }
 
-    fn toggle_player(&mut self) {
-        self.current_player = match self.current_player {
-            Naught => Cross,
-            Cross => Naught,
-            Empty => panic!("Player can't be empty"),
-        };
+    pub fn status(&self) -> GameStatus {
+        if let Some(by) = self.winner {
+            // The full run is only needed for display, so it's found by
+            // walking the board.
+            let line = self.get_winning_line().unwrap_or_default();
+            return GameStatus::Won { by, line };
+        }
+
+        if self.is_full() {
+            return GameStatus::Draw;
+        }
+
+        GameStatus::InProgress {
+            next: self.current_player,
+        }
     }
-}
 
-fn all_match(symbols: &[Symbol]) -> Option<Symbol> {
-    let symbol = symbols.first().unwrap();
-    if matches!(symbol, Empty) {
-        return None;
+    fn get_winning_line(&self) -> Option<Vec<usize>> {
+        self.find_winning_lines(true).pop()
+    }
+
+    /// Finds runs of at least `win_length` matching symbols. Each run is only
+    /// walked from its first square, so every square is visited at most once
+    /// per direction whatever the board size.
+    fn find_winning_lines(&self, first_only: bool) -> Vec<Vec<usize>> {
+        let mut lines = Vec::new();
+
+        for start in 0..self.board.len() {
+            let symbol = self.board[start];
+            if symbol == Empty {
+                continue;
+            }
+
+            for direction in DIRECTIONS.iter() {
+                let (row_step, column_step) = *direction;
+                let previous = self.step(start, (-row_step, -column_step));
+                if previous.map(|index| self.board[index]) == Some(symbol) {
+                    continue;
+                }
+
+                let mut line = vec![start];
+                let mut current = start;
+                while let Some(next) = self.step(current, *direction) {
+                    if self.board[next] != symbol {
+                        break;
+                    }
+                    line.push(next);
+                    current = next;
+                }
+
+                if line.len() >= self.rules.win_length {
+                    lines.push(line);
+                    if first_only {
+                        return lines;
+                    }
+                }
+            }
+        }
+
+        lines
     }
-    if symbols.iter().all(|i| i == symbol) {
-        return Some(symbol.to_owned());
+
+    /// Moves one square from `index` in the given direction, or `None` if
+    /// that would leave the board.
+    fn step(&self, index: usize, (row_step, column_step): (isize, isize)) -> Option<usize> {
+        let row = (index / self.rules.width) as isize + row_step;
+        let column = (index % self.rules.width) as isize + column_step;
+
+        if row < 0
+            || column < 0
+            || row >= self.rules.height as isize
+            || column >= self.rules.width as isize
+        {
+            return None;
+        }
+
+        Some(row as usize * self.rules.width + column as usize)
+    }
+
+    fn toggle_player(&mut self) {
+        self.current_player = self.current_player.other();
     }
-    return None;
 }
 
-#[derive(Clone, PartialEq, Debug, Copy)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Copy)]
 pub enum Symbol {
     Empty,
     Cross,
     Naught,
 }
 
+impl Symbol {
+    /// Whose piece this is, or `None` for an empty square.
+    pub fn player(self) -> Option<Player> {
+        match self {
+            Empty => None,
+            Cross => Some(Player::Cross),
+            Naught => Some(Player::Naught),
+        }
+    }
+}
+
+/// One of the two sides. Unlike `Symbol` it can't be empty, so the player
+/// to move or the winner is always someone who can play.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Player {
+    Cross,
+    Naught,
+}
+
+impl Player {
+    pub fn other(self) -> Player {
+        match self {
+            Player::Cross => Player::Naught,
+            Player::Naught => Player::Cross,
+        }
+    }
+}
+
+impl From<Player> for Symbol {
+    fn from(player: Player) -> Symbol {
+        match player {
+            Player::Cross => Cross,
+            Player::Naught => Naught,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum GameStatus {
+    InProgress { next: Player },
+    Won { by: Player, line: Vec<usize> },
+    Draw,
+}
+
+/// Parses a classic 3x3 position such as `"XO.X.O..."`, reading the board
+/// row by row with `.` for an empty square. `/` can be used between rows.
+impl FromStr for Game {
+    type Err = PositionError;
+
+    fn from_str(text: &str) -> Result<Game, PositionError> {
+        let board = text
+            .chars()
+            .filter(|c| *c != '/')
+            .map(|c| match c {
+                'X' => Ok(Cross),
+                'O' => Ok(Naught),
+                '.' => Ok(Empty),
+                _ => Err(PositionError::InvalidSquare(c)),
+            })
+            .collect::<Result<Vec<Symbol>, PositionError>>()?;
+
+        Game::from_board(Rules::classic(), board)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RulesError {
+    EmptyBoard,
+    WinLengthDoesNotFit,
+    TooManySquares,
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RulesError::EmptyBoard => write!(f, "the board needs at least one square"),
+            RulesError::WinLengthDoesNotFit => {
+                write!(f, "the win length doesn't fit on the board")
+            }
+            RulesError::TooManySquares => {
+                write!(f, "the board can have at most {} squares", MAX_SQUARES)
+            }
+        }
+    }
+}
+
+impl Error for RulesError {}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PositionError {
+    WrongSize { expected: usize, found: usize },
+    InvalidSquare(char),
+    PieceCounts { crosses: usize, naughts: usize },
+    BothPlayersWon,
+    PlayedAfterWin,
+    TooManyLines,
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PositionError::WrongSize { expected, found } => write!(
+                f,
+                "the board has {} squares but the rules need {}",
+                found, expected
+            ),
+            PositionError::InvalidSquare(c) => {
+                write!(f, "'{}' is not a square, expected X, O or .", c)
+            }
+            PositionError::PieceCounts { crosses, naughts } => write!(
+                f,
+                "{} crosses and {} naughts can't happen with players taking turns",
+                crosses, naughts
+            ),
+            PositionError::BothPlayersWon => write!(f, "both players have a winning line"),
+            PositionError::PlayedAfterWin => {
+                write!(f, "a move was played after the game was won")
+            }
+            PositionError::TooManyLines => {
+                write!(f, "the winning lines couldn't all be made by one move")
+            }
+        }
+    }
+}
+
+impl Error for PositionError {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum PlayError {
-    SquareTaken,
-    OutOfRange,
+    /// The square already has `by`'s piece on it.
+    SquareTaken {
+        index: usize,
+        by: Player,
+    },
+    /// The square isn't on a board with these rules.
+    OutOfRange {
+        square: Square,
+        rules: Rules,
+    },
+    GameOver,
 }
 
+impl fmt::Display for PlayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlayError::SquareTaken { index, by } => {
+                write!(f, "square {} is already taken by {}", index, by)
+            }
+            PlayError::OutOfRange {
+                square: Square::Index(index),
+                rules,
+            } => write!(
+                f,
+                "square {} is off the board, which has squares 0 to {}",
+                index,
+                rules.squares() - 1
+            ),
+            PlayError::OutOfRange {
+                square: Square::Coord(coord),
+                rules,
+            } => write!(
+                f,
+                "square {} is off the board, which goes from A1 to {}",
+                coord,
+                Coord::from_index(rules.squares() - 1, *rules)
+            ),
+            PlayError::GameOver => write!(f, "the game is already over"),
+        }
+    }
+}
+
+impl Error for PlayError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::play_on;
     use rstest::rstest;
 
     #[test]
@@ -142,12 +666,13 @@ mod tests {
     fn when_position_is_out_of_range_board_is_unchanged_and_error_is_reported() {
         let mut game = Game::start();
 
-        let is_out_of_range = match game.play(9) {
-            Err(PlayError::OutOfRange) => true,
-            _ => false,
-        };
-
-        assert!(is_out_of_range, "Out of range error should be returned");
+        assert_eq!(
+            game.play(9),
+            Err(PlayError::OutOfRange {
+                square: Square::Index(9),
+                rules: Rules::classic()
+            })
+        );
         assert_eq!(
             game.board,
             vec![Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty]
@@ -159,12 +684,13 @@ mod tests {
         let mut game = Game::start();
         game.play(0).ok();
 
-        let is_square_taken = match game.play(0) {
-            Err(PlayError::SquareTaken) => true,
-            _ => false,
-        };
-
-        assert!(is_square_taken, "SquareTakenError should be returned");
+        assert_eq!(
+            game.play(0),
+            Err(PlayError::SquareTaken {
+                index: 0,
+                by: Player::Cross
+            })
+        );
         assert_eq!(
             game.board,
             vec![Cross, Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty]
@@ -185,6 +711,29 @@ mod tests {
         );
     }
 
+    // Sets up a board without checking it could be reached.
+    fn unchecked_game(board: Vec<Symbol>) -> Game {
+        let mut game = Game::start();
+        for (index, symbol) in board.into_iter().enumerate() {
+            game.set_square(index, symbol);
+        }
+        game.winner = game.find_winner();
+        game
+    }
+
+    // The winner's pieces on these boards are exactly the winning line, and
+    // the other player must not be reported as having won too.
+    fn assert_wins(board: Vec<Symbol>, winner: Symbol) {
+        let line: Vec<usize> = (0..board.len())
+            .filter(|index| board[*index] == winner)
+            .collect();
+        let game = unchecked_game(board);
+        let by = winner.player().unwrap();
+
+        assert_eq!(game.get_winner(), Some(by));
+        assert_eq!(game.status(), GameStatus::Won { by, line });
+    }
+
     #[rstest]
     #[case(vec![Cross, Cross, Cross, Empty, Empty, Empty, Empty, Empty, Empty], Cross)]
     #[case(vec![Empty, Empty, Empty, Cross, Cross, Cross, Empty, Empty, Empty], Cross)]
@@ -192,22 +741,11 @@ mod tests {
     #[case(vec![Naught, Naught, Naught, Empty, Empty, Empty, Empty, Empty, Empty], Naught)]
     #[case(vec![Empty, Empty, Empty, Naught, Naught, Naught, Empty, Empty, Empty], Naught)]
     #[case(vec![Empty, Empty, Empty, Empty, Empty, Empty, Naught, Naught, Naught], Naught)]
-
     fn matching_symbol_in_row_returns_winner(
         #[case] initial_board: Vec<Symbol>,
         #[case] expected_winner: Symbol,
     ) {
-        let initial_board = initial_board;
-
-        let game = Game {
-            board: initial_board,
-            current_player: Cross,
-        };
-
-        let winner = game.get_winner().unwrap();
-        let winners_match = matches!(winner, expected_winner);
-
-        assert_eq!(winners_match, true);
+        assert_wins(initial_board, expected_winner);
     }
 
     #[rstest]
@@ -221,17 +759,7 @@ mod tests {
         #[case] initial_board: Vec<Symbol>,
         #[case] expected_winner: Symbol,
     ) {
-        let initial_board = initial_board;
-
-        let game = Game {
-            board: initial_board,
-            current_player: Cross,
-        };
-
-        let winner = game.get_winner().unwrap();
-        let winners_match = matches!(winner, expected_winner);
-
-        assert_eq!(winners_match, true);
+        assert_wins(initial_board, expected_winner);
     }
 
     #[rstest]
@@ -243,16 +771,538 @@ mod tests {
         #[case] initial_board: Vec<Symbol>,
         #[case] expected_winner: Symbol,
     ) {
-        let initial_board = initial_board;
+        assert_wins(initial_board, expected_winner);
+    }
 
-        let game = Game {
-            board: initial_board,
-            current_player: Cross,
-        };
+    #[test]
+    fn available_moves_are_the_empty_squares() {
+        let mut game = Game::start();
+        game.play(0).ok();
+        game.play(4).ok();
+
+        assert_eq!(game.available_moves(), vec![1, 2, 3, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn new_game_is_in_progress_with_cross_to_play() {
+        let game = Game::start();
+
+        assert_eq!(
+            game.status(),
+            GameStatus::InProgress {
+                next: Player::Cross
+            }
+        );
+    }
 
-        let winner = game.get_winner().unwrap();
-        let winners_match = matches!(winner, expected_winner);
+    #[test]
+    fn status_reports_the_next_player() {
+        let mut game = Game::start();
+        game.play(4).ok();
 
-        assert_eq!(winners_match, true);
+        assert_eq!(
+            game.status(),
+            GameStatus::InProgress {
+                next: Player::Naught
+            }
+        );
+    }
+
+    #[test]
+    fn status_reports_the_winner_and_winning_line() {
+        let game: Game = "OXX/.OX/..O".parse().unwrap();
+
+        assert_eq!(
+            game.status(),
+            GameStatus::Won {
+                by: Player::Naught,
+                line: vec![0, 4, 8]
+            }
+        );
+    }
+
+    #[test]
+    fn full_board_without_a_winner_is_a_draw() {
+        let game: Game = "XOX/XOO/OXX".parse().unwrap();
+
+        assert_eq!(game.get_winner(), None);
+        assert_eq!(game.status(), GameStatus::Draw);
+    }
+
+    #[test]
+    fn cannot_play_once_the_game_is_won() {
+        let mut game = Game::start();
+        play_on(&mut game, &[0, 3, 1, 4, 2]);
+
+        let is_game_over = matches!(game.play(8), Err(PlayError::GameOver));
+
+        assert!(is_game_over, "GameOver error should be returned");
+        assert_eq!(game.board[8], Empty);
+    }
+
+    #[test]
+    fn cannot_play_once_the_game_is_drawn() {
+        let mut game: Game = "XOX/XOO/OXX".parse().unwrap();
+
+        let is_game_over = matches!(game.play(0), Err(PlayError::GameOver));
+
+        assert!(is_game_over, "GameOver error should be returned");
+    }
+
+    #[rstest]
+    #[case(0, 3, 3)]
+    #[case(3, 0, 3)]
+    fn board_must_have_squares(#[case] width: usize, #[case] height: usize, #[case] k: usize) {
+        assert_eq!(Rules::new(width, height, k), Err(RulesError::EmptyBoard));
+    }
+
+    #[rstest]
+    #[case(3, 3, 0)]
+    #[case(3, 3, 4)]
+    #[case(4, 2, 5)]
+    fn win_length_must_fit_on_the_board(
+        #[case] width: usize,
+        #[case] height: usize,
+        #[case] k: usize,
+    ) {
+        assert_eq!(
+            Rules::new(width, height, k),
+            Err(RulesError::WinLengthDoesNotFit)
+        );
+    }
+
+    #[test]
+    fn boards_are_limited_to_max_squares() {
+        assert!(Rules::new(20, 20, 5).is_ok());
+        assert!(Rules::new(100, 100, 5).is_ok());
+        assert_eq!(Rules::new(101, 100, 5), Err(RulesError::TooManySquares));
+    }
+
+    #[test]
+    fn wins_are_found_anywhere_on_a_big_board() {
+        let mut game = Game::with_rules(100, 100, 5).unwrap();
+
+        // Down the last column, each piece in a different word.
+        for row in 95..100 {
+            game.play_at(row, 99).unwrap();
+            if row < 99 {
+                game.play_at(row, 0).unwrap();
+            }
+        }
+
+        assert_eq!(game.get_winner(), Some(Player::Cross));
+    }
+
+    #[rstest]
+    #[case(100_000, 100_000)]
+    #[case(usize::MAX, 2)]
+    #[case(2, usize::MAX)]
+    fn huge_boards_are_rejected(#[case] width: usize, #[case] height: usize) {
+        assert_eq!(
+            Rules::new(width, height, 3),
+            Err(RulesError::TooManySquares)
+        );
+    }
+
+    #[test]
+    fn custom_rules_create_a_board_of_that_size() {
+        let game = Game::with_rules(4, 2, 3).unwrap();
+
+        assert_eq!(game.board().len(), 8);
+        assert_eq!(game.rules().width(), 4);
+        assert_eq!(game.rules().height(), 2);
+        assert_eq!(game.rules().win_length(), 3);
+    }
+
+    #[test]
+    fn out_of_range_depends_on_board_size() {
+        let mut game = Game::with_rules(4, 4, 3).unwrap();
+
+        assert!(game.play(15).is_ok());
+        assert!(matches!(game.play(16), Err(PlayError::OutOfRange { .. })));
+    }
+
+    #[test]
+    fn gomoku_needs_five_in_a_row() {
+        let mut game = Game::with_rules(15, 15, 5).unwrap();
+        // X builds along row 7 while O plays along row 0.
+        play_on(&mut game, &[105, 0, 106, 1, 107, 2, 108, 3]);
+
+        assert_eq!(game.get_winner(), None);
+
+        game.play(109).ok();
+
+        assert_eq!(
+            game.status(),
+            GameStatus::Won {
+                by: Player::Cross,
+                line: vec![105, 106, 107, 108, 109]
+            }
+        );
+    }
+
+    #[rstest]
+    // Down a column.
+    #[case(vec![1, 0, 6, 2, 11], vec![1, 6, 11])]
+    // Down-right diagonal not starting in a corner.
+    #[case(vec![6, 0, 12, 1, 18], vec![6, 12, 18])]
+    // Down-left diagonal.
+    #[case(vec![4, 0, 8, 1, 12], vec![4, 8, 12])]
+    fn k_in_a_row_is_found_in_every_direction(
+        #[case] moves: Vec<usize>,
+        #[case] expected_line: Vec<usize>,
+    ) {
+        let mut game = Game::with_rules(5, 5, 3).unwrap();
+        play_on(&mut game, &moves);
+
+        assert_eq!(
+            game.status(),
+            GameStatus::Won {
+                by: Player::Cross,
+                line: expected_line
+            }
+        );
+    }
+
+    #[test]
+    fn lines_do_not_wrap_around_the_edge_of_the_board() {
+        let mut game = Game::with_rules(4, 4, 3).unwrap();
+        // X has the last two squares of row 0 and the first of row 1.
+        play_on(&mut game, &[2, 8, 3, 9, 4]);
+
+        assert_eq!(game.get_winner(), None);
+    }
+
+    #[test]
+    fn moves_are_recorded_in_order() {
+        let mut game = Game::start();
+        play_on(&mut game, &[4, 0, 8]);
+        game.play(8).ok();
+
+        assert_eq!(game.history(), &[4, 0, 8]);
+    }
+
+    #[test]
+    fn undo_restores_the_board_and_player() {
+        let mut game = Game::start();
+        play_on(&mut game, &[4, 0]);
+
+        assert_eq!(game.undo(), Some(0));
+        assert_eq!(game.board[0], Empty);
+        assert_eq!(
+            game.status(),
+            GameStatus::InProgress {
+                next: Player::Naught
+            }
+        );
+        assert_eq!(game.history(), &[4]);
+
+        assert_eq!(game.undo(), Some(4));
+        assert_eq!(game.undo(), None);
+        assert_eq!(game.board, Game::start().board);
+        assert_eq!(game.current_player, Player::Cross);
+    }
+
+    #[test]
+    fn redo_replays_undone_moves() {
+        let mut game = Game::start();
+        play_on(&mut game, &[4, 0, 8]);
+        let before_undo = game.clone();
+
+        game.undo();
+        game.undo();
+
+        assert_eq!(game.redo(), Some(0));
+        assert_eq!(game.redo(), Some(8));
+        assert_eq!(game.redo(), None);
+        assert_eq!(game, before_undo);
+    }
+
+    #[test]
+    fn turns_against_the_computer_undo_its_reply_too() {
+        let mut game = Game::start();
+        play_on(&mut game, &[4, 0, 8]);
+
+        assert!(game.undo_turn(Some(Player::Naught)));
+        assert_eq!(game.history(), &[4, 0]);
+        assert!(game.undo_turn(Some(Player::Naught)));
+        assert_eq!(game.history(), &[] as &[usize]);
+        assert!(!game.undo_turn(Some(Player::Naught)));
+
+        assert!(game.redo_turn(Some(Player::Naught)));
+        assert_eq!(game.history(), &[4, 0]);
+        assert!(game.redo_turn(Some(Player::Naught)));
+        assert_eq!(game.history(), &[4, 0, 8]);
+        assert!(!game.redo_turn(Some(Player::Naught)));
+    }
+
+    #[test]
+    fn turns_between_people_are_one_move() {
+        let mut game = Game::start();
+        play_on(&mut game, &[4, 0]);
+
+        assert!(game.undo_turn(None));
+        assert_eq!(game.history(), &[4]);
+        assert!(game.redo_turn(None));
+        assert_eq!(game.history(), &[4, 0]);
+    }
+
+    #[test]
+    fn playing_a_new_move_clears_redo() {
+        let mut game = Game::start();
+        play_on(&mut game, &[4, 0]);
+        game.undo();
+        game.play(2).ok();
+
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.history(), &[4, 2]);
+    }
+
+    #[test]
+    fn undo_reopens_a_finished_game() {
+        let mut game = Game::start();
+        play_on(&mut game, &[0, 3, 1, 4, 2]);
+
+        game.undo();
+
+        assert_eq!(
+            game.status(),
+            GameStatus::InProgress {
+                next: Player::Cross
+            }
+        );
+        assert!(game.play(8).is_ok());
+    }
+
+    #[test]
+    fn redo_wins_the_game_again() {
+        let mut game = Game::start();
+        play_on(&mut game, &[0, 3, 1, 4, 2]);
+
+        game.undo();
+        game.redo();
+
+        assert_eq!(game.get_winner(), Some(Player::Cross));
+        assert!(matches!(game.play(8), Err(PlayError::GameOver)));
+    }
+
+    #[rstest]
+    #[case("XO.X.O...", Player::Cross)]
+    #[case("X........", Player::Naught)]
+    #[case(".........", Player::Cross)]
+    fn side_to_move_comes_from_the_piece_counts(#[case] board: &str, #[case] next: Player) {
+        let game: Game = board.parse().unwrap();
+
+        assert_eq!(game.status(), GameStatus::InProgress { next });
+    }
+
+    #[test]
+    fn positions_can_be_built_from_a_board() {
+        let board = vec![
+            Cross, Empty, Empty, Empty, Naught, Empty, Empty, Empty, Cross,
+        ];
+        let game = Game::from_board(Rules::classic(), board.clone()).unwrap();
+
+        assert_eq!(game.board(), board.as_slice());
+        assert_eq!(game.current_player(), Player::Naught);
+        assert_eq!(game.history(), &[] as &[usize]);
+    }
+
+    #[test]
+    fn naughts_can_move_first() {
+        let mut game = Game::starting_with(Rules::classic(), Player::Naught);
+        assert_eq!(game.current_player(), Player::Naught);
+
+        game.play(4).ok();
+
+        assert_eq!(game.board()[4], Naught);
+        assert_eq!(game.current_player(), Player::Cross);
+    }
+
+    #[rstest]
+    #[case(vec![Naught, Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty], Player::Cross)]
+    #[case(vec![Naught, Cross, Empty, Empty, Empty, Empty, Empty, Empty, Empty], Player::Naught)]
+    fn positions_can_start_with_naughts(#[case] board: Vec<Symbol>, #[case] next: Player) {
+        let game = Game::from_board_starting_with(Rules::classic(), board, Player::Naught).unwrap();
+
+        assert_eq!(game.current_player(), next);
+    }
+
+    #[test]
+    fn piece_counts_depend_on_who_started() {
+        let board = vec![
+            Cross, Empty, Empty, Empty, Empty, Empty, Empty, Empty, Empty,
+        ];
+
+        assert_eq!(
+            Game::from_board_starting_with(Rules::classic(), board, Player::Naught),
+            Err(PositionError::PieceCounts {
+                crosses: 1,
+                naughts: 0
+            })
+        );
+    }
+
+    #[test]
+    fn positions_can_use_custom_rules() {
+        let rules = Rules::new(4, 2, 3).unwrap();
+        let board = vec![Cross, Cross, Cross, Empty, Naught, Naught, Empty, Empty];
+
+        let game = Game::from_board(rules, board).unwrap();
+
+        assert_eq!(game.get_winner(), Some(Player::Cross));
+    }
+
+    #[test]
+    fn board_must_fit_the_rules() {
+        assert_eq!(
+            Game::from_board(Rules::classic(), vec![Empty; 8]),
+            Err(PositionError::WrongSize {
+                expected: 9,
+                found: 8
+            })
+        );
+        assert_eq!(
+            "XO.X.O..".parse::<Game>(),
+            Err(PositionError::WrongSize {
+                expected: 9,
+                found: 8
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_characters_are_rejected() {
+        assert_eq!(
+            "XO.X.O..Z".parse::<Game>(),
+            Err(PositionError::InvalidSquare('Z'))
+        );
+    }
+
+    #[rstest]
+    #[case("XX.......", 2, 0)]
+    #[case("O........", 0, 1)]
+    #[case("XXXO.....", 3, 1)]
+    fn piece_counts_must_be_possible(
+        #[case] board: &str,
+        #[case] crosses: usize,
+        #[case] naughts: usize,
+    ) {
+        assert_eq!(
+            board.parse::<Game>(),
+            Err(PositionError::PieceCounts { crosses, naughts })
+        );
+    }
+
+    #[test]
+    fn both_players_cannot_have_won() {
+        assert_eq!(
+            "XXX/OOO/...".parse::<Game>(),
+            Err(PositionError::BothPlayersWon)
+        );
+    }
+
+    #[rstest]
+    // X completed the top row but O has played since.
+    #[case("XXX/OO./..O")]
+    // O completed the middle row but X has played since.
+    #[case("X.X/OOO/X.X")]
+    fn no_moves_after_a_win(#[case] board: &str) {
+        assert_eq!(board.parse::<Game>(), Err(PositionError::PlayedAfterWin));
+    }
+
+    #[test]
+    fn winning_lines_must_share_the_last_move() {
+        let rules = Rules::new(5, 3, 3).unwrap();
+        // X has three in a row at the top and bottom, no single move made both.
+        let board = vec![
+            Cross, Cross, Cross, Empty, Naught, //
+            Naught, Naught, Empty, Naught, Naught, //
+            Empty, Empty, Cross, Cross, Cross,
+        ];
+
+        assert_eq!(
+            Game::from_board(rules, board),
+            Err(PositionError::TooManyLines)
+        );
+    }
+
+    #[test]
+    fn two_lines_made_by_one_move_are_allowed() {
+        // X's last move in the corner finished the top row and left column.
+        let game: Game = "XXX/XOO/XOO".parse().unwrap();
+
+        assert_eq!(game.get_winner(), Some(Player::Cross));
+    }
+
+    #[test]
+    fn either_player_can_be_made_to_play() {
+        let mut game = Game::start();
+
+        assert!(game.play_as(Player::Naught, 0).is_ok());
+        assert!(game.play_as(Player::Naught, 1).is_ok());
+        assert_eq!(
+            game.play_as(Player::Cross, 1),
+            Err(PlayError::SquareTaken {
+                index: 1,
+                by: Player::Naught
+            })
+        );
+        assert!(game.play_as(Player::Naught, 2).is_ok());
+
+        assert_eq!(game.get_winner(), Some(Player::Naught));
+    }
+
+    #[test]
+    fn play_errors_describe_the_square() {
+        assert_eq!(
+            PlayError::SquareTaken {
+                index: 4,
+                by: Player::Naught
+            }
+            .to_string(),
+            "square 4 is already taken by O"
+        );
+        assert_eq!(
+            PlayError::OutOfRange {
+                square: Square::Coord(Coord { row: 0, col: 3 }),
+                rules: Rules::classic()
+            }
+            .to_string(),
+            "square D1 is off the board, which goes from A1 to C3"
+        );
+        assert_eq!(
+            PlayError::OutOfRange {
+                square: Square::Index(16),
+                rules: Rules::new(4, 4, 3).unwrap()
+            }
+            .to_string(),
+            "square 16 is off the board, which has squares 0 to 15"
+        );
+        assert_eq!(PlayError::GameOver.to_string(), "the game is already over");
+    }
+
+    #[test]
+    fn play_at_uses_row_and_column() {
+        let mut game = Game::with_rules(4, 2, 3).unwrap();
+
+        assert!(game.play_at(1, 2).is_ok());
+        assert_eq!(game.board[6], Cross);
+    }
+
+    #[rstest]
+    #[case(3, 0)]
+    #[case(0, 3)]
+    fn play_at_off_the_board_is_out_of_range(#[case] row: usize, #[case] col: usize) {
+        let mut game = Game::start();
+
+        assert_eq!(
+            game.play_at(row, col),
+            Err(PlayError::OutOfRange {
+                square: Square::Coord(Coord { row, col }),
+                rules: Rules::classic()
+            })
+        );
+        assert_eq!(game.board, Game::start().board);
     }
 }
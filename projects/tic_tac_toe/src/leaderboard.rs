@@ -0,0 +1,376 @@
+//! Results for named players, kept in a text file between runs. Each line is
+//! a player's name, wins, losses, draws and rating separated by tabs, e.g.
+//!
+//! `Alice<tab>3<tab>1<tab>0<tab>1046.2`
+//!
+//! Ratings use the Elo system: everyone starts at 1000 and each game moves
+//! both players' ratings by up to 32 points, more for an upset.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+const STARTING_RATING: f64 = 1000.0;
+const K_FACTOR: f64 = 32.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub rating: f64,
+}
+
+impl Default for Record {
+    fn default() -> Record {
+        Record {
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            rating: STARTING_RATING,
+        }
+    }
+}
+
+impl Record {
+    pub fn played(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    /// The share of games won, from 0 to 1.
+    pub fn win_rate(&self) -> f64 {
+        match self.played() {
+            0 => 0.0,
+            played => f64::from(self.wins) / f64::from(played),
+        }
+    }
+}
+
+#[derive(Default, PartialEq, Debug)]
+pub struct Leaderboard {
+    players: BTreeMap<String, Record>,
+}
+
+impl Leaderboard {
+    /// Reads the leaderboard stored at `path`, returning it along with the
+    /// number of lines that couldn't be read and were skipped. A missing
+    /// file is an empty leaderboard.
+    pub fn load(path: &Path) -> io::Result<(Leaderboard, usize)> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(Leaderboard::parse(&text)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                Ok((Leaderboard::default(), 0))
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn parse(text: &str) -> (Leaderboard, usize) {
+        let mut leaderboard = Leaderboard::default();
+        let mut skipped = 0;
+
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            match parse_record(line) {
+                Some((name, record)) => {
+                    leaderboard.players.insert(name, record);
+                }
+                None => skipped += 1,
+            }
+        }
+
+        (leaderboard, skipped)
+    }
+
+    /// Writes the leaderboard to a temporary file first, so a crash part
+    /// way through leaves the old file in place. The temporary file is named
+    /// after the process so two games finishing at once don't collide.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut text = String::new();
+        for (name, record) in self.players.iter() {
+            text.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{:.1}\n",
+                name, record.wins, record.losses, record.draws, record.rating
+            ));
+        }
+
+        let temporary = path.with_extension(format!("{}.tmp", process::id()));
+        fs::write(&temporary, text)?;
+        fs::rename(&temporary, path)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Record> {
+        self.players.get(name)
+    }
+
+    /// Records a game between two players from `player`'s point of view.
+    /// Games against yourself aren't counted.
+    pub fn record(&mut self, player: &str, opponent: &str, outcome: Outcome) {
+        if player == opponent {
+            return;
+        }
+
+        let player_rating = self.rating(player);
+        let opponent_rating = self.rating(opponent);
+        let expected = 1.0 / (1.0 + 10f64.powf((opponent_rating - player_rating) / 400.0));
+        let actual = match outcome {
+            Outcome::Win => 1.0,
+            Outcome::Draw => 0.5,
+            Outcome::Loss => 0.0,
+        };
+        let change = K_FACTOR * (actual - expected);
+
+        let record = self.players.entry(player.to_string()).or_default();
+        record.rating += change;
+        match outcome {
+            Outcome::Win => record.wins += 1,
+            Outcome::Loss => record.losses += 1,
+            Outcome::Draw => record.draws += 1,
+        }
+
+        let record = self.players.entry(opponent.to_string()).or_default();
+        record.rating -= change;
+        match outcome {
+            Outcome::Win => record.losses += 1,
+            Outcome::Loss => record.wins += 1,
+            Outcome::Draw => record.draws += 1,
+        }
+    }
+
+    fn rating(&self, name: &str) -> f64 {
+        self.players
+            .get(name)
+            .map_or(STARTING_RATING, |record| record.rating)
+    }
+
+    /// Players from highest rated to lowest, with names breaking ties.
+    pub fn ranked(&self) -> Vec<(&str, &Record)> {
+        let mut ranked: Vec<(&str, &Record)> = self
+            .players
+            .iter()
+            .map(|(name, record)| (name.as_str(), record))
+            .collect();
+
+        ranked.sort_by(|(_, a), (_, b)| b.rating.partial_cmp(&a.rating).unwrap_or(Ordering::Equal));
+
+        ranked
+    }
+
+    pub fn table(&self) -> Vec<String> {
+        let ranked = self.ranked();
+        if ranked.is_empty() {
+            return vec![String::from("No games have been recorded yet")];
+        }
+
+        let name_width = ranked
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("Player".len());
+
+        let mut lines = vec![format!(
+            "{:<4} {:<width$} {:>6} {:>4} {:>4} {:>4} {:>8}",
+            "Rank",
+            "Player",
+            "Rating",
+            "W",
+            "L",
+            "D",
+            "Win rate",
+            width = name_width
+        )];
+
+        for (rank, (name, record)) in ranked.iter().enumerate() {
+            lines.push(format!(
+                "{:<4} {:<width$} {:>6.0} {:>4} {:>4} {:>4} {:>7.0}%",
+                rank + 1,
+                name,
+                record.rating,
+                record.wins,
+                record.losses,
+                record.draws,
+                record.win_rate() * 100.0,
+                width = name_width
+            ));
+        }
+
+        lines
+    }
+}
+
+/// Names are stored as a single field, so they can't be blank or contain
+/// tabs or line breaks.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.trim().is_empty() && !name.contains(['\t', '\n', '\r'])
+}
+
+fn parse_record(line: &str) -> Option<(String, Record)> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() != 5 || !is_valid_name(fields[0]) {
+        return None;
+    }
+
+    let rating: f64 = fields[4].trim().parse().ok()?;
+    if !rating.is_finite() {
+        return None;
+    }
+
+    let record = Record {
+        wins: fields[1].trim().parse().ok()?,
+        losses: fields[2].trim().parse().ok()?,
+        draws: fields[3].trim().parse().ok()?,
+        rating,
+    };
+
+    Some((fields[0].to_string(), record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::path::PathBuf;
+
+    fn temp_file(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("tic_tac_toe_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn new_players_start_at_the_same_rating() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record("Alice", "Bob", Outcome::Win);
+
+        let alice = leaderboard.get("Alice").unwrap();
+        let bob = leaderboard.get("Bob").unwrap();
+        assert_eq!((alice.wins, alice.losses, alice.draws), (1, 0, 0));
+        assert_eq!((bob.wins, bob.losses, bob.draws), (0, 1, 0));
+        assert_eq!(alice.rating, 1016.0);
+        assert_eq!(bob.rating, 984.0);
+    }
+
+    #[test]
+    fn beating_a_stronger_player_gains_more() {
+        let mut leaderboard = Leaderboard::default();
+        for _ in 0..5 {
+            leaderboard.record("Alice", "Bob", Outcome::Win);
+        }
+        let before = leaderboard.get("Bob").unwrap().rating;
+
+        leaderboard.record("Bob", "Alice", Outcome::Win);
+
+        assert!(leaderboard.get("Bob").unwrap().rating - before > 16.0);
+    }
+
+    #[test]
+    fn draws_move_ratings_towards_each_other() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record("Alice", "Bob", Outcome::Win);
+        leaderboard.record("Alice", "Bob", Outcome::Draw);
+
+        let alice = leaderboard.get("Alice").unwrap();
+        assert_eq!(alice.draws, 1);
+        assert!(alice.rating < 1016.0);
+        assert_eq!(
+            alice.rating + leaderboard.get("Bob").unwrap().rating,
+            2000.0
+        );
+    }
+
+    #[test]
+    fn games_against_yourself_are_ignored() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record("Alice", "Alice", Outcome::Win);
+
+        assert_eq!(leaderboard, Leaderboard::default());
+    }
+
+    #[test]
+    fn players_are_ranked_by_rating() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record("Bob", "Alice", Outcome::Win);
+        leaderboard.record("Carol", "Alice", Outcome::Draw);
+
+        let names: Vec<&str> = leaderboard.ranked().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["Bob", "Carol", "Alice"]);
+    }
+
+    #[test]
+    fn table_shows_each_player() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record("Alice", "Bob", Outcome::Win);
+
+        assert_eq!(
+            leaderboard.table(),
+            [
+                "Rank Player Rating    W    L    D Win rate",
+                "1    Alice    1016    1    0    0     100%",
+                "2    Bob       984    0    1    0       0%",
+            ]
+        );
+        assert_eq!(
+            Leaderboard::default().table(),
+            ["No games have been recorded yet"]
+        );
+    }
+
+    #[test]
+    fn leaderboards_round_trip_through_a_file() {
+        let path = temp_file("leaderboard_round_trip.txt");
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record("Alice", "Bob", Outcome::Win);
+        leaderboard.record("Bob", "Carol Smith", Outcome::Draw);
+
+        leaderboard.save(&path).unwrap();
+        let (loaded, skipped) = Leaderboard::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(skipped, 0);
+        assert_eq!(loaded.ranked().len(), 3);
+        assert_eq!(loaded.get("Alice").unwrap().wins, 1);
+        assert_eq!(loaded.get("Carol Smith").unwrap().draws, 1);
+    }
+
+    #[test]
+    fn missing_files_are_empty() {
+        let (leaderboard, skipped) = Leaderboard::load(&temp_file("no_such_file.txt")).unwrap();
+
+        assert_eq!(leaderboard, Leaderboard::default());
+        assert_eq!(skipped, 0);
+    }
+
+    #[test]
+    fn unreadable_lines_are_skipped() {
+        let text = concat!(
+            "Alice\t1\t0\t0\t1016.0\n",
+            "garbage\n",
+            "Bob\tone\t0\t0\t984.0\n",
+            "Carol\t0\t0\t0\tNaN\n",
+            "\n",
+            "Dave\t0\t1\t0\t984.0\n",
+        );
+
+        let (leaderboard, skipped) = Leaderboard::parse(text);
+
+        assert_eq!(skipped, 3);
+        assert_eq!(leaderboard.ranked().len(), 2);
+        assert_eq!(leaderboard.get("Dave").unwrap().losses, 1);
+    }
+
+    #[test]
+    fn names_must_fit_in_one_field() {
+        assert!(is_valid_name("Alice"));
+        assert!(is_valid_name("Carol Smith"));
+        assert!(!is_valid_name("  "));
+        assert!(!is_valid_name("Tab\tName"));
+    }
+}
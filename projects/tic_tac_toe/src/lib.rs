@@ -0,0 +1,35 @@
+//! The tic-tac-toe engine: boards of any size, the rule variants, the save
+//! file notation, opening books, series of games and the computer opponent.
+//! The `tic_tac_toe` binary is a front-end over this crate.
+//!
+//! ```
+//! use tic_tac_toe::{Game, GameStatus, Player};
+//!
+//! let mut game = Game::start();
+//! for index in [0, 3, 1, 4, 2].iter() {
+//!     game.play(*index).ok();
+//! }
+//!
+//! assert_eq!(game.get_winner(), Some(Player::Cross));
+//! assert!(matches!(game.status(), GameStatus::Won { .. }));
+//! ```
+
+pub mod analysis;
+mod bitboard;
+pub mod book;
+pub mod computer;
+pub mod coord;
+pub mod display;
+pub mod game;
+pub mod notation;
+pub mod replay;
+pub mod series;
+pub mod symmetry;
+#[cfg(test)]
+mod testing;
+pub mod ultimate;
+pub mod variant;
+
+pub use computer::{Computer, Difficulty};
+pub use game::{Game, GameStatus, PlayError, Player, PositionError, Rules, RulesError, Symbol};
+pub use notation::NotationError;
@@ -1,13 +1,940 @@
-mod game;
-use std::io;
+mod args;
+mod error;
+mod leaderboard;
+mod net;
+mod tui;
+use args::{Args, Mode};
+use error::CliError;
+use leaderboard::{Leaderboard, Outcome};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use tic_tac_toe::book::Book;
+use tic_tac_toe::computer::{Computer, Difficulty};
+use tic_tac_toe::coord::{self, Coord, Square, SquareError};
+use tic_tac_toe::game::{Game, GameStatus, Player, Rules, Symbol};
+use tic_tac_toe::replay::{self, Quality, Replay};
+use tic_tac_toe::series::Match;
+use tic_tac_toe::ultimate::Ultimate;
+use tic_tac_toe::variant::{Kind, Misere, Numerical, Variant, Wild};
+use tic_tac_toe::{analysis, notation};
 
 fn main() {
+    if let Err(error) = run() {
+        eprintln!("{}", error);
+        process::exit(error.exit_code());
+    }
+}
+
+fn run() -> Result<(), CliError> {
+    let args = Args::parse(env::args().skip(1))?;
+
+    let game = match (args.size, args.win_length) {
+        (None, None) => Game::start(),
+        (size, win_length) => {
+            let (width, height) = size.unwrap_or((3, 3));
+            Game::with_rules(width, height, win_length.unwrap_or(3))?
+        }
+    };
+
+    if args.variant == Kind::Numerical && game.rules() != Rules::classic() {
+        return Err(CliError::Usage(String::from(
+            "Numerical tic-tac-toe is only played on a 3x3 board",
+        )));
+    }
+    // Only the menu knows how to play the variants.
+    if args.variant != Kind::Standard && (args.tui || args.mode != Mode::Local) {
+        return Err(CliError::Usage(String::from(
+            "--variant only works with the menu, not with --tui or a subcommand",
+        )));
+    }
+
+    match &args.mode {
+        Mode::Local => {}
+        Mode::Serve(address) => {
+            return net::serve(address, game).map_err(|source| CliError::Io {
+                action: format!("run the server on {}", address),
+                source,
+            });
+        }
+        Mode::Analyze(position) => return analyze(position, args.colour),
+        Mode::Replay(path) => return replay(path, game.rules(), args.colour),
+        Mode::GenerateBook(path) => {
+            return generate_book(path, game.rules(), args.depth.unwrap_or(BOOK_DEPTH))
+        }
+        Mode::Join(address) => {
+            return net::join(address, args.colour).map_err(|source| CliError::Io {
+                action: format!("play on {}", address),
+                source,
+            });
+        }
+    }
+
+    if args.tui {
+        return tui::run(game).map_err(|source| CliError::Io {
+            action: String::from("run the full screen mode"),
+            source,
+        });
+    }
+
+    let book = match &args.book {
+        Some(path) => Some(load_book(path, game.rules())?),
+        None => None,
+    };
+
     println!("Tic Tac Toe");
+    let rules = game.rules();
+    if rules != Rules::classic() {
+        println!(
+            "{}x{} board, {} in a row to win",
+            rules.width(),
+            rules.height(),
+            rules.win_length()
+        );
+    }
+    if args.variant != Kind::Standard {
+        println!("{}", args.variant.description());
+    }
     println!("1) Play vs Human");
+    println!("2) Play vs Computer");
+    println!("3) Leaderboard");
+    println!("4) Play Ultimate Tic Tac Toe");
+
+    menu(&args, rules, book.as_ref()).map_err(|source| CliError::Io {
+        action: String::from("use the terminal"),
+        source,
+    })
+}
+
+/// Reads the menu choice and plays it.
+fn menu(args: &Args, rules: Rules, book: Option<&Book>) -> io::Result<()> {
+    let selection = match read_line()? {
+        Some(selection) => selection,
+        None => return Ok(()),
+    };
+
+    let leaderboard = leaderboard_path(args);
+    let selection = selection.trim();
+    match selection {
+        "1" | "2" => {}
+        "3" => {
+            for line in load_leaderboard(&leaderboard).table() {
+                println!("{}", line);
+            }
+            return Ok(());
+        }
+        "4" => return play_ultimate(args.colour),
+        other => {
+            println!("Unknown option '{}'", other);
+            return Ok(());
+        }
+    }
+
+    if args.variant != Kind::Standard {
+        return play_variant_game(args, rules, selection == "2", book);
+    }
+
+    let games = match args.best_of {
+        Some(games) => games,
+        None => match choose_series_length()? {
+            Some(games) => games,
+            None => return Ok(()),
+        },
+    };
+    let series = Match::best_of(rules, games);
+
+    if selection == "1" {
+        let cross = match ask_name("Name for X", "Player X")? {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        let naught = match ask_name("Name for O", "Player O")? {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        let players = Players { cross, naught };
+        play_match(series, None, &players, &leaderboard, args.colour)
+    } else {
+        play_vs_computer(series, args, &leaderboard, book)
+    }
+}
+
+/// The names playing each side, used to record results.
+struct Players {
+    cross: String,
+    naught: String,
+}
+
+impl Players {
+    fn name(&self, player: Player) -> &str {
+        match player {
+            Player::Cross => &self.cross,
+            Player::Naught => &self.naught,
+        }
+    }
+}
+
+/// The leaderboard file from `--leaderboard`, otherwise one in the home
+/// directory, or the current directory if there isn't a home directory.
+fn leaderboard_path(args: &Args) -> PathBuf {
+    if let Some(path) = &args.leaderboard {
+        return PathBuf::from(path);
+    }
+
+    match env::var_os("HOME") {
+        Some(home) => Path::new(&home).join(".tic_tac_toe_leaderboard"),
+        None => PathBuf::from("tic_tac_toe_leaderboard.txt"),
+    }
+}
+
+/// Loads the leaderboard, carrying on with whatever could be read if the file
+/// is damaged.
+fn load_leaderboard(path: &Path) -> Leaderboard {
+    match Leaderboard::load(path) {
+        Ok((leaderboard, 0)) => leaderboard,
+        Ok((leaderboard, skipped)) => {
+            println!(
+                "Skipped {} unreadable lines in the leaderboard at {}",
+                skipped,
+                path.display()
+            );
+            leaderboard
+        }
+        Err(error) => {
+            println!(
+                "Couldn't read the leaderboard at {}: {}",
+                path.display(),
+                error
+            );
+            Leaderboard::default()
+        }
+    }
+}
+
+fn record_result(path: &Path, players: &Players, game: &Game) {
+    let outcome = match game.status() {
+        GameStatus::Won {
+            by: Player::Cross, ..
+        } => Outcome::Win,
+        GameStatus::Won { .. } => Outcome::Loss,
+        GameStatus::Draw => Outcome::Draw,
+        GameStatus::InProgress { .. } => return,
+    };
+
+    let mut leaderboard = load_leaderboard(path);
+    leaderboard.record(&players.cross, &players.naught, outcome);
+
+    if let Err(error) = leaderboard.save(path) {
+        println!(
+            "Couldn't save the leaderboard to {}: {}",
+            path.display(),
+            error
+        );
+        return;
+    }
+
+    let ratings: Vec<String> = [&players.cross, &players.naught]
+        .iter()
+        .filter_map(|name| {
+            let record = leaderboard.get(name)?;
+            Some(format!("{} {:.0}", name, record.rating))
+        })
+        .collect();
+    if !ratings.is_empty() {
+        println!("Ratings: {}", ratings.join(", "));
+    }
+}
+
+/// Asks how many games to play, unless `--best-of` already said.
+fn choose_series_length() -> io::Result<Option<u32>> {
+    loop {
+        print!("How many games? Best of [1]: ");
+        io::stdout().flush()?;
+
+        let answer = match read_line()? {
+            Some(answer) => answer,
+            None => return Ok(None),
+        };
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Ok(Some(1));
+        }
+
+        match answer.parse() {
+            Ok(games) if games > 0 => return Ok(Some(games)),
+            _ => println!("'{}' is not a number of games", answer),
+        }
+    }
+}
+
+fn ask_name(question: &str, default: &str) -> io::Result<Option<String>> {
+    loop {
+        print!("{} [{}]: ", question, default);
+        io::stdout().flush()?;
+
+        let answer = match read_line()? {
+            Some(answer) => answer,
+            None => return Ok(None),
+        };
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Ok(Some(default.to_string()));
+        }
+
+        if leaderboard::is_valid_name(answer) {
+            return Ok(Some(answer.to_string()));
+        }
+        println!("Names can't be blank or contain tabs or line breaks");
+    }
+}
+
+/// How many moves into the game `book` goes without `--depth`.
+const BOOK_DEPTH: usize = 2;
+
+/// Works out the best move in every position up to `depth` moves in and
+/// saves them as an opening book.
+fn generate_book(path: &str, rules: Rules, depth: usize) -> Result<(), CliError> {
+    println!("Searching every position up to {} moves in...", depth);
+    let book = Book::generate(rules, depth);
+
+    fs::write(path, book.to_bytes()).map_err(|source| CliError::Io {
+        action: format!("write {}", path),
+        source,
+    })?;
+    println!("Wrote {} positions to {}", book.len(), path);
+
+    Ok(())
+}
+
+fn load_book(path: &str, rules: Rules) -> Result<Book, CliError> {
+    let bytes = fs::read(path).map_err(|source| CliError::Io {
+        action: format!("read {}", path),
+        source,
+    })?;
+
+    Book::from_bytes(&bytes, rules).map_err(|source| CliError::InvalidBook {
+        path: path.to_string(),
+        source,
+    })
+}
+
+/// Prints the board and the solver's verdict on each move. Positions can be
+/// given in the save file notation or as a 3x3 board such as `X../.O./...`.
+fn analyze(position: &str, colour: bool) -> Result<(), CliError> {
+    let game = if position.trim().contains(' ') {
+        notation::parse(position).map_err(|error| CliError::InvalidGame {
+            description: format!("'{}' is not a valid game", position),
+            source: Box::new(error),
+        })?
+    } else {
+        position
+            .parse::<Game>()
+            .map_err(|error| CliError::InvalidGame {
+                description: format!("'{}' is not a valid position", position),
+                source: Box::new(error),
+            })?
+    };
+
+    let empty_squares = game.available_moves().len();
+    if empty_squares > analysis::MAX_EMPTY_SQUARES {
+        return Err(CliError::Usage(format!(
+            "The position has {} empty squares, at most {} can be analyzed",
+            empty_squares,
+            analysis::MAX_EMPTY_SQUARES
+        )));
+    }
+
+    if colour {
+        println!("{:#}", game);
+    } else {
+        println!("{}", game);
+    }
+    for line in analysis::report(&game) {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+/// Steps through a recorded game, with the solver's verdict on each move.
+/// Games can be a save file or a list of moves such as `4, 0, 8`, played on
+/// the board given by `--size` and `--win`.
+fn replay(path: &str, rules: Rules, colour: bool) -> Result<(), CliError> {
+    let text = fs::read_to_string(path).map_err(|source| CliError::Io {
+        action: format!("read {}", path),
+        source,
+    })?;
+    let game = replay::read_game(text.trim(), rules).map_err(|error| CliError::InvalidGame {
+        description: format!("{} doesn't contain a valid game", path),
+        source: Box::new(error),
+    })?;
+
+    let mut replay = Replay::new(game);
+    step_through(&mut replay, colour).map_err(|source| CliError::Io {
+        action: String::from("use the terminal"),
+        source,
+    })
+}
+
+fn step_through(replay: &mut Replay, colour: bool) -> io::Result<()> {
+    println!("Commands: Enter or n for the next move, b to go back, s for the start, e for the end, q to quit");
+
+    loop {
+        if colour {
+            println!("\n{:#}", replay.game());
+        } else {
+            println!("\n{}", replay.game());
+        }
+        println!("{}", describe_replay_move(replay));
+
+        print!("> ");
+        io::stdout().flush()?;
+
+        let command = match read_line()? {
+            Some(line) => line.trim().to_lowercase(),
+            None => return Ok(()),
+        };
+
+        match command.as_str() {
+            "" | "n" => {
+                if !replay.forward() {
+                    println!("That was the last move");
+                }
+            }
+            "b" => {
+                if !replay.back() {
+                    println!("Already at the start");
+                }
+            }
+            "s" => replay.rewind(),
+            "e" => replay.fast_forward(),
+            "q" => return Ok(()),
+            _ => println!("'{}' is not a command", command),
+        }
+    }
+}
+
+fn describe_replay_move(replay: &Replay) -> String {
+    let rules = replay.game().rules();
+    let (square, annotation) = match replay.last_move() {
+        Some(last_move) => last_move,
+        None => return format!("Start of the game, {} moves to play", replay.moves()),
+    };
+
+    let mut description = format!(
+        "Move {} of {}: {} played {} ({})",
+        replay.position(),
+        replay.moves(),
+        replay.game().board()[square],
+        Coord::from_index(square, rules),
+        square
+    );
+
+    if let Some(annotation) = annotation {
+        description += &format!(
+            ", {} ({})",
+            annotation.quality,
+            annotation.played.describe(annotation.player)
+        );
+
+        if annotation.quality != Quality::Best {
+            description += &format!(
+                ", {} ({}) was best ({})",
+                Coord::from_index(annotation.best_move, rules),
+                annotation.best_move,
+                annotation.best.describe(annotation.player)
+            );
+        }
+    }
+
+    description
+}
+
+fn play_vs_computer(
+    series: Match,
+    args: &Args,
+    leaderboard: &Path,
+    book: Option<&Book>,
+) -> io::Result<()> {
+    let name = match ask_name("Your name", "Player")? {
+        Some(name) => name,
+        None => return Ok(()),
+    };
+
+    let (computer, opponent) = match choose_computer(args, book)? {
+        Some(computer) => computer,
+        None => return Ok(()),
+    };
+
+    let players = if computer == Player::Cross {
+        Players {
+            cross: opponent.name(),
+            naught: name,
+        }
+    } else {
+        Players {
+            cross: name,
+            naught: opponent.name(),
+        }
+    };
+
+    play_match(
+        series,
+        Some((computer, opponent)),
+        &players,
+        leaderboard,
+        args.colour,
+    )
+}
+
+/// Sets up the computer opponent, asking for the difficulty unless it was
+/// given on the command line, and which side it plays.
+fn choose_computer(args: &Args, book: Option<&Book>) -> io::Result<Option<(Player, Computer)>> {
+    let difficulty = match args.difficulty {
+        Some(difficulty) => difficulty,
+        None => match choose_difficulty()? {
+            Some(difficulty) => difficulty,
+            None => return Ok(None),
+        },
+    };
+
+    let opponent = match args.seed {
+        Some(seed) => Computer::with_seed(difficulty, seed),
+        None => Computer::new(difficulty),
+    };
+    let opponent = match book {
+        Some(book) => opponent.with_book(book.clone()),
+        None => opponent,
+    };
+
+    print!("Do you want to go first? (y/n): ");
+    io::stdout().flush()?;
+
+    let answer = match read_line()? {
+        Some(answer) => answer,
+        None => return Ok(None),
+    };
+    if answer.trim().eq_ignore_ascii_case("n") {
+        Ok(Some((Player::Cross, opponent)))
+    } else {
+        Ok(Some((Player::Naught, opponent)))
+    }
+}
+
+/// Plays games until the match is decided, printing the score after each
+/// one when there's more than one game.
+fn play_match(
+    mut series: Match,
+    mut computer: Option<(Player, Computer)>,
+    players: &Players,
+    leaderboard: &Path,
+    colour: bool,
+) -> io::Result<()> {
+    while let Some(game) = series.next_game() {
+        if series.games() > 1 {
+            println!(
+                "\nGame {} of {}, {} goes first",
+                series.score().played() + 1,
+                series.games(),
+                game.current_player()
+            );
+        }
+
+        let game = match play_game(game, &mut computer, colour)? {
+            Some(game) => game,
+            None => return Ok(()),
+        };
+        series.record(&game);
+        record_result(leaderboard, players, &game);
+
+        if series.games() > 1 {
+            println!("{}", series.scoreboard());
+        }
+    }
+
+    if series.games() > 1 {
+        match series.winner() {
+            Some(winner) => println!("{} wins the match!", players.name(winner)),
+            None => println!("The match is drawn"),
+        }
+    }
+
+    Ok(())
+}
+
+fn choose_difficulty() -> io::Result<Option<Difficulty>> {
+    loop {
+        print!("Choose a difficulty (easy/medium/hard) [hard]: ");
+        io::stdout().flush()?;
+
+        let answer = match read_line()? {
+            Some(answer) => answer,
+            None => return Ok(None),
+        };
+        if answer.trim().is_empty() {
+            return Ok(Some(Difficulty::Hard));
+        }
+
+        match answer.trim().parse() {
+            Ok(difficulty) => return Ok(Some(difficulty)),
+            Err(message) => println!("{}", message),
+        }
+    }
+}
+
+/// Runs a game to completion, with the computer choosing moves for its symbol
+/// and everything else read from stdin. Returns the finished game, or `None`
+/// if input ran out first.
+fn play_game(
+    mut game: Game,
+    computer: &mut Option<(Player, Computer)>,
+    colour: bool,
+) -> io::Result<Option<Game>> {
+    println!("Commands: u to undo, r to redo, save <file>, load <file>");
+
+    loop {
+        if colour {
+            println!("\n{:#}", game);
+        } else {
+            println!("\n{}", game);
+        }
+
+        let next = match game.status() {
+            GameStatus::InProgress { next } => next,
+            GameStatus::Won { by, .. } => {
+                println!("{} wins!", by);
+                print_history(&game);
+                return Ok(Some(game));
+            }
+            GameStatus::Draw => {
+                println!("It's a draw!");
+                print_history(&game);
+                return Ok(Some(game));
+            }
+        };
+
+        if let Some((symbol, opponent)) = computer.as_mut() {
+            if *symbol == next {
+                if let Some(index) = opponent.choose_move(&game) {
+                    println!(
+                        "Computer plays {} ({})",
+                        index,
+                        Coord::from_index(index, game.rules())
+                    );
+                    game.play(index).ok();
+                }
+                continue;
+            }
+        }
+
+        print!("{}: ", game.prompt());
+        io::stdout().flush()?;
+
+        let input = match read_line()? {
+            Some(input) => input,
+            None => return Ok(None),
+        };
+
+        let computer_symbol = computer.as_ref().map(|(symbol, _)| *symbol);
+        let command = input.trim();
+        if command == "u" {
+            if !game.undo_turn(computer_symbol) {
+                println!("Nothing to undo");
+            }
+            continue;
+        }
+        if command == "r" {
+            if !game.redo_turn(computer_symbol) {
+                println!("Nothing to redo");
+            }
+            continue;
+        }
+        if let Some(path) = command.strip_prefix("save ") {
+            save_game(&game, path.trim());
+            continue;
+        }
+        if let Some(path) = command.strip_prefix("load ") {
+            if let Some(loaded) = load_game(path.trim()) {
+                game = loaded;
+            }
+            continue;
+        }
+
+        let result = match command.parse::<Square>() {
+            Ok(square) => square.play(&mut game),
+            Err(error) => {
+                println!("{}", error);
+                continue;
+            }
+        };
+
+        if let Err(error) = result {
+            println!("Can't play there, {}", error);
+        }
+    }
+}
+
+/// Runs a game of ultimate tic-tac-toe between two people at the keyboard.
+fn play_ultimate(colour: bool) -> io::Result<()> {
+    println!("The square you play in picks the board your opponent plays on next");
+    let mut game = Ultimate::start();
+
+    loop {
+        if colour {
+            println!("\n{:#}", game);
+        } else {
+            println!("\n{}", game);
+        }
+        if game
+            .meta()
+            .board()
+            .iter()
+            .any(|cell| *cell != Symbol::Empty)
+        {
+            println!("Boards won:");
+            if colour {
+                println!("{:#}", game.meta());
+            } else {
+                println!("{}", game.meta());
+            }
+        }
+
+        let next = match game.status() {
+            GameStatus::InProgress { next } => next,
+            GameStatus::Won { by, .. } => {
+                println!("{} wins!", by);
+                return Ok(());
+            }
+            GameStatus::Draw => {
+                println!("It's a draw!");
+                return Ok(());
+            }
+        };
+
+        match game.next_board() {
+            Some(board) => print!(
+                "{} to play on board {} ({}), choose a square (0-8 or A1-C3): ",
+                next,
+                Coord::from_index(board, Rules::classic()),
+                board
+            ),
+            None => print!(
+                "{} to play on any open board, choose a board and a square (e.g. B2 A1): ",
+                next
+            ),
+        }
+        io::stdout().flush()?;
+
+        let input = match read_line()? {
+            Some(input) => input,
+            None => return Ok(()),
+        };
+
+        let squares: Result<Vec<usize>, SquareError> = input
+            .split_whitespace()
+            .map(|text| coord::parse_square(text, Rules::classic()))
+            .collect();
+        let (board, square) = match (squares, game.next_board()) {
+            (Ok(squares), _) if squares.len() == 2 => (squares[0], squares[1]),
+            (Ok(squares), Some(board)) if squares.len() == 1 => (board, squares[0]),
+            (Ok(_), _) => {
+                println!("Choose a board and a square, e.g. B2 A1");
+                continue;
+            }
+            (Err(error), _) => {
+                println!("Can't play there, {}", error);
+                continue;
+            }
+        };
+
+        if let Err(error) = game.play(board, square) {
+            println!("Can't play there, {}", error);
+        }
+    }
+}
+
+/// Plays a single game of one of the rule variants. Results aren't rated,
+/// so there's no series or leaderboard.
+fn play_variant_game(
+    args: &Args,
+    rules: Rules,
+    vs_computer: bool,
+    book: Option<&Book>,
+) -> io::Result<()> {
+    let computer = if vs_computer {
+        match choose_computer(args, book)? {
+            Some(computer) => Some(computer),
+            None => return Ok(()),
+        }
+    } else {
+        None
+    };
+
+    match args.variant {
+        Kind::Standard => play_variant(
+            Game::starting_with(rules, Player::Cross),
+            computer,
+            args.colour,
+        ),
+        Kind::Misere => play_variant(Misere::new(rules), computer, args.colour),
+        Kind::Wild => play_variant(Wild::new(rules), computer, args.colour),
+        Kind::Numerical => play_variant(Numerical::new(), computer, args.colour),
+    }
+}
+
+/// What the terminal needs to play a variant: how to ask for a move.
+trait Interactive: Variant + fmt::Display {
+    fn prompt(&self) -> String;
+}
+
+impl Interactive for Game {
+    fn prompt(&self) -> String {
+        let last_square = self.board().len() - 1;
+        format!(
+            "{} to play, choose a square (0-{} or A1-{})",
+            self.current_player(),
+            last_square,
+            Coord::from_index(last_square, self.rules())
+        )
+    }
+}
+
+impl Interactive for Misere {
+    fn prompt(&self) -> String {
+        self.game().prompt()
+    }
+}
+
+impl Interactive for Wild {
+    fn prompt(&self) -> String {
+        format!(
+            "{} to play, choose a square and X or O (e.g. B2 O)",
+            self.current_player()
+        )
+    }
+}
+
+impl Interactive for Numerical {
+    fn prompt(&self) -> String {
+        let player = self.current_player();
+        let numbers: Vec<String> = self
+            .numbers_left(player)
+            .iter()
+            .map(u8::to_string)
+            .collect();
+        format!(
+            "{} to play, choose a square and one of {} (e.g. B2 {})",
+            player,
+            numbers.join(", "),
+            numbers.first().map_or("", String::as_str)
+        )
+    }
+}
+
+/// Runs a variant game to completion, with the computer choosing moves for
+/// its symbol and everything else read from stdin.
+fn play_variant<V: Interactive>(
+    mut game: V,
+    mut computer: Option<(Player, Computer)>,
+    colour: bool,
+) -> io::Result<()> {
+    loop {
+        if colour {
+            println!("\n{:#}", game);
+        } else {
+            println!("\n{}", game);
+        }
+
+        let next = match game.status() {
+            GameStatus::InProgress { next } => next,
+            GameStatus::Won { by, .. } => {
+                println!("{} wins!", by);
+                return Ok(());
+            }
+            GameStatus::Draw => {
+                println!("It's a draw!");
+                return Ok(());
+            }
+        };
+
+        if let Some((symbol, opponent)) = computer.as_mut() {
+            if *symbol == next {
+                if let Some(mv) = opponent.choose_move(&game) {
+                    println!("Computer plays {}", mv);
+                    game.apply(mv).ok();
+                }
+                continue;
+            }
+        }
+
+        print!("{}: ", game.prompt());
+        io::stdout().flush()?;
+
+        let input = match read_line()? {
+            Some(input) => input,
+            None => return Ok(()),
+        };
+
+        match game.parse_move(input.trim()) {
+            Ok(mv) => {
+                if let Err(error) = game.apply(mv) {
+                    println!("{} can't be played, {}", mv, error);
+                }
+            }
+            Err(error) => println!("Can't read that move, {}", error),
+        }
+    }
+}
+
+fn save_game(game: &Game, path: &str) {
+    match fs::write(path, notation::to_string(game) + "\n") {
+        Ok(()) => println!("Saved game to {}", path),
+        Err(error) => println!("Couldn't save to {}: {}", path, error),
+    }
+}
+
+fn load_game(path: &str) -> Option<Game> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) => {
+            println!("Couldn't read {}: {}", path, error);
+            return None;
+        }
+    };
+
+    match notation::parse(text.trim()) {
+        Ok(game) => {
+            println!("Loaded game from {}", path);
+            Some(game)
+        }
+        Err(error) => {
+            println!("{} doesn't contain a valid game: {}", path, error);
+            None
+        }
+    }
+}
+
+fn print_history(game: &Game) {
+    let moves: Vec<String> = game
+        .history()
+        .iter()
+        .map(|index| index.to_string())
+        .collect();
+    println!("Moves played: {}", moves.join(", "));
+}
+
+/// Reads a line from stdin, returning `None` once input is exhausted.
+fn read_line() -> io::Result<Option<String>> {
+    let mut line = String::new();
+
+    let bytes_read = io::stdin().read_line(&mut line)?;
 
-    let mut selection = String::new();
+    if bytes_read == 0 {
+        return Ok(None);
+    }
 
-    io::stdin()
-        .read_line(&mut selection)
-        .expect("Failed to read line");
+    Ok(Some(line))
 }
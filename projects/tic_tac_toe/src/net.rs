@@ -0,0 +1,430 @@
+//! Playing over TCP. The server owns the game and the two clients send it
+//! moves. Messages are single lines of text.
+//!
+//! Server to client:
+//!
+//! - `WELCOME <X|O>`: the symbol this client plays
+//! - `WAIT`: waiting for an opponent to connect
+//! - `STATE <game>`: the game in the save file notation, sent after every move
+//! - `ERROR <message>`: the last message was rejected
+//! - `OVER <X|O|DRAW>`: the game has finished
+//! - `BYE`: the opponent left, so the game is abandoned
+//!
+//! Client to server:
+//!
+//! - `MOVE <square>`: a board index or coordinate such as `B2`
+//! - `QUIT`: leave the game
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use tic_tac_toe::coord::{Coord, Square};
+use tic_tac_toe::game::{Game, GameStatus, Player};
+use tic_tac_toe::notation;
+
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+/// The longest message a client may send. Moves are short, so anything
+/// longer is treated as the client leaving.
+const MAX_CLIENT_MESSAGE: usize = 256;
+
+/// The longest message the server may send, enough for the `STATE` of the
+/// largest board with every square in its history.
+const MAX_SERVER_MESSAGE: usize = 128 * 1024;
+
+pub fn serve(address: &str, game: Game) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    println!("Listening on {}", listener.local_addr()?);
+
+    host(listener, game)
+}
+
+/// Waits for two players on `listener` then runs one game between them.
+fn host(listener: TcpListener, mut game: Game) -> io::Result<()> {
+    // Each player gets a thread reading their messages, so a player leaving
+    // is noticed even while it's their opponent's turn. `None` means the
+    // connection closed.
+    let (sender, messages) = mpsc::channel();
+    let mut writers = Vec::new();
+
+    for symbol in [Player::Cross, Player::Naught].iter().copied() {
+        let (stream, peer) = listener.accept()?;
+        println!("{} connected from {}", symbol, peer);
+
+        let mut writer = stream.try_clone()?;
+        send(&mut writer, &format!("WELCOME {}", symbol));
+        if symbol == Player::Cross {
+            send(&mut writer, "WAIT");
+        }
+        writers.push((symbol, writer));
+
+        let sender = sender.clone();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stream);
+            while let Ok(Some(line)) = read_message(&mut reader, MAX_CLIENT_MESSAGE) {
+                sender.send((symbol, Some(line))).ok();
+            }
+            sender.send((symbol, None)).ok();
+        });
+    }
+
+    broadcast(
+        &mut writers,
+        &format!("STATE {}", notation::to_string(&game)),
+    );
+
+    for (symbol, message) in messages {
+        let line = match message {
+            Some(line) if line.trim() != "QUIT" => line,
+            _ => {
+                println!("{} left the game", symbol);
+                broadcast(&mut writers, "BYE");
+                return Ok(());
+            }
+        };
+
+        let reply = match line.trim().strip_prefix("MOVE ") {
+            Some(_) if game.current_player() != symbol => Err(String::from("It's not your turn")),
+            Some(square) => play(&mut game, square.trim()),
+            None => Err(format!("Unknown message '{}'", line.trim())),
+        };
+
+        if let Err(message) = reply {
+            let writer = writers
+                .iter_mut()
+                .find(|(player, _)| *player == symbol)
+                .map(|(_, writer)| writer);
+            if let Some(writer) = writer {
+                send(writer, &format!("ERROR {}", message));
+            }
+            continue;
+        }
+
+        broadcast(
+            &mut writers,
+            &format!("STATE {}", notation::to_string(&game)),
+        );
+        match game.status() {
+            GameStatus::Won { by, .. } => {
+                println!("{} wins!", by);
+                broadcast(&mut writers, &format!("OVER {}", by));
+                return Ok(());
+            }
+            GameStatus::Draw => {
+                println!("It's a draw!");
+                broadcast(&mut writers, "OVER DRAW");
+                return Ok(());
+            }
+            GameStatus::InProgress { .. } => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn play(game: &mut Game, square: &str) -> Result<(), String> {
+    let parsed: Square = square.parse().map_err(|error| format!("{}", error))?;
+
+    parsed
+        .play(game)
+        .map_err(|error| format!("Can't play there, {}", error))
+}
+
+fn broadcast(writers: &mut [(Player, TcpStream)], message: &str) {
+    for (_, writer) in writers.iter_mut() {
+        send(writer, message);
+    }
+}
+
+/// Sends one message, ignoring failures since a closed connection is
+/// noticed by its reader thread instead.
+fn send(writer: &mut TcpStream, message: &str) {
+    writeln!(writer, "{}", message).ok();
+}
+
+/// Reads one message, without its line ending. Lines longer than `limit`
+/// bytes are an error so a peer can't make us buffer without end. `None`
+/// means the connection closed.
+fn read_message(reader: &mut impl BufRead, limit: usize) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    let read = reader
+        .by_ref()
+        .take(limit as u64 + 1)
+        .read_line(&mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if read > limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message longer than {} bytes", limit),
+        ));
+    }
+
+    let line = line.strip_suffix('\n').unwrap_or(&line);
+    Ok(Some(line.strip_suffix('\r').unwrap_or(line).to_string()))
+}
+
+/// Parses a `STATE` message. Its rules are checked before the board is
+/// built: the first state may be any size the rules allow, but later ones
+/// must keep to the same board.
+fn parse_state(text: &str, current: Option<&Game>) -> io::Result<Game> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let field = text.split_whitespace().next().unwrap_or("");
+    let rules = notation::parse_rules(field).map_err(|error| invalid(error.to_string()))?;
+    if let Some(current) = current {
+        if current.rules() != rules {
+            return Err(invalid(format!(
+                "the server changed the board to {} mid-game",
+                field
+            )));
+        }
+    }
+
+    notation::parse(text).map_err(|error| invalid(error.to_string()))
+}
+
+/// Connects to a server and plays, reading moves from stdin.
+pub fn join(address: &str, colour: bool) -> io::Result<()> {
+    let stream = TcpStream::connect(address)?;
+    let mut writer = stream.try_clone()?;
+    let mut me = None;
+    let mut game = None;
+
+    let mut reader = BufReader::new(stream);
+    while let Some(line) = read_message(&mut reader, MAX_SERVER_MESSAGE)? {
+        let (kind, rest) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+
+        match kind {
+            "WELCOME" => {
+                let player = if rest == "O" {
+                    Player::Naught
+                } else {
+                    Player::Cross
+                };
+                println!("Connected to {}, you are {}", address, player);
+                me = Some(player);
+            }
+            "WAIT" => println!("Waiting for an opponent to join..."),
+            "STATE" => {
+                let state = parse_state(rest, game.as_ref())?;
+                if colour {
+                    println!("\n{:#}", state);
+                } else {
+                    println!("\n{}", state);
+                }
+                game = Some(state);
+            }
+            "ERROR" => println!("{}", rest),
+            "OVER" => {
+                match rest {
+                    "DRAW" => println!("It's a draw!"),
+                    winner => println!("{} wins!", winner),
+                }
+                return Ok(());
+            }
+            "BYE" => {
+                println!("Your opponent left the game");
+                return Ok(());
+            }
+            _ => continue,
+        }
+
+        // Prompt after a new state or a rejected move if it's our turn.
+        let current = match game.as_ref() {
+            Some(current) if kind == "STATE" || kind == "ERROR" => current,
+            _ => continue,
+        };
+        match current.status() {
+            GameStatus::InProgress { next } if Some(next) == me => match prompt(current) {
+                Some(square) => send(&mut writer, &format!("MOVE {}", square)),
+                None => {
+                    send(&mut writer, "QUIT");
+                    return Ok(());
+                }
+            },
+            GameStatus::InProgress { next } if kind == "STATE" => {
+                println!("Waiting for {} to play...", next)
+            }
+            _ => {}
+        }
+    }
+
+    println!("Lost the connection to the server");
+    Ok(())
+}
+
+/// Asks for a square, returning `None` once stdin is exhausted.
+fn prompt(game: &Game) -> Option<String> {
+    let last_square = game.board().len() - 1;
+    print!(
+        "{} to play, choose a square (0-{} or A1-{}): ",
+        game.current_player(),
+        last_square,
+        Coord::from_index(last_square, game.rules())
+    );
+    io::stdout().flush().ok()?;
+
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Client {
+        fn connect(address: &str) -> Client {
+            let stream = TcpStream::connect(address).unwrap();
+            Client {
+                writer: stream.try_clone().unwrap(),
+                reader: BufReader::new(stream),
+            }
+        }
+
+        fn send(&mut self, message: &str) {
+            writeln!(self.writer, "{}", message).unwrap();
+        }
+
+        fn receive(&mut self) -> String {
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            line.trim_end().to_string()
+        }
+    }
+
+    /// Starts a server on a free port and connects both players, skipping
+    /// past the greetings and the opening state.
+    fn start() -> (thread::JoinHandle<io::Result<()>>, Client, Client) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || host(listener, Game::start()));
+
+        let mut cross = Client::connect(&address);
+        assert_eq!(cross.receive(), "WELCOME X");
+        assert_eq!(cross.receive(), "WAIT");
+
+        let mut naught = Client::connect(&address);
+        assert_eq!(naught.receive(), "WELCOME O");
+
+        let opening = "STATE 3x3:3 .../.../... X - -";
+        assert_eq!(cross.receive(), opening);
+        assert_eq!(naught.receive(), opening);
+
+        (server, cross, naught)
+    }
+
+    #[test]
+    fn moves_are_relayed_to_both_players() {
+        let (_server, mut cross, mut naught) = start();
+
+        cross.send("MOVE B2");
+
+        assert_eq!(cross.receive(), "STATE 3x3:3 .../.X./... O 4 -");
+        assert_eq!(naught.receive(), "STATE 3x3:3 .../.X./... O 4 -");
+    }
+
+    #[test]
+    fn bad_moves_are_rejected_without_changing_the_game() {
+        let (_server, mut cross, mut naught) = start();
+
+        naught.send("MOVE 0");
+        assert_eq!(naught.receive(), "ERROR It's not your turn");
+
+        cross.send("MOVE 9");
+        assert_eq!(
+            cross.receive(),
+            "ERROR Can't play there, square 9 is off the board, which has squares 0 to 8"
+        );
+
+        cross.send("MOVE Z");
+        assert_eq!(
+            cross.receive(),
+            "ERROR 'Z' is not a square, expected e.g. B2 or 2,2"
+        );
+
+        cross.send("HELLO");
+        assert_eq!(cross.receive(), "ERROR Unknown message 'HELLO'");
+
+        cross.send("MOVE 0");
+        naught.receive();
+        naught.send("MOVE 0");
+        assert_eq!(
+            naught.receive(),
+            "ERROR Can't play there, square 0 is already taken by X"
+        );
+    }
+
+    #[test]
+    fn finished_games_are_announced() {
+        let (server, mut cross, mut naught) = start();
+
+        for (index, square) in [0, 3, 1, 4, 2].iter().enumerate() {
+            let player = if index % 2 == 0 {
+                &mut cross
+            } else {
+                &mut naught
+            };
+            player.send(&format!("MOVE {}", square));
+            cross.receive();
+            naught.receive();
+        }
+
+        assert_eq!(cross.receive(), "OVER X");
+        assert_eq!(naught.receive(), "OVER X");
+        assert!(server.join().unwrap().is_ok());
+    }
+
+    #[test]
+    fn overlong_messages_end_the_game() {
+        let (server, mut cross, mut naught) = start();
+
+        cross.send(&format!("MOVE {}", "1".repeat(MAX_CLIENT_MESSAGE)));
+
+        assert_eq!(naught.receive(), "BYE");
+        assert!(server.join().unwrap().is_ok());
+    }
+
+    #[test]
+    fn messages_are_read_up_to_the_limit() {
+        let mut reader = "MOVE 4\r\nQUIT\nMOVE 12345\n".as_bytes();
+
+        assert_eq!(read_message(&mut reader, 8).unwrap().unwrap(), "MOVE 4");
+        assert_eq!(read_message(&mut reader, 8).unwrap().unwrap(), "QUIT");
+        assert!(read_message(&mut reader, 8).is_err());
+    }
+
+    #[test]
+    fn a_closed_connection_has_no_message() {
+        assert_eq!(read_message(&mut "".as_bytes(), 8).unwrap(), None);
+    }
+
+    #[test]
+    fn states_must_keep_to_the_same_board() {
+        let first = parse_state("3x3:3 .../.X./... O 4 -", None).unwrap();
+
+        assert!(parse_state("3x3:3 .../.XO/... X 4,5 -", Some(&first)).is_ok());
+        assert!(parse_state("4x4:3 ..../..../..../.... X - -", Some(&first)).is_err());
+        assert!(parse_state("100000x100000:3 . X - -", None).is_err());
+    }
+
+    #[test]
+    fn leaving_ends_the_game_for_the_opponent() {
+        let (server, cross, mut naught) = start();
+
+        drop(cross);
+
+        assert_eq!(naught.receive(), "BYE");
+        assert!(server.join().unwrap().is_ok());
+    }
+}
@@ -0,0 +1,442 @@
+//! A one line text notation for saving and sharing games, e.g.
+//!
+//! `3x3:3 X../.O./... X 0,4 8`
+//!
+//! The fields are the rules (`<width>x<height>:<win length>`), the board with
+//! rows separated by `/`, the player to move, the moves played so far and the
+//! moves available to redo, next redo first. Empty move lists are written as
+//! `-`. Any pieces on the board that aren't in the history are the position
+//! the game was set up from. Players take turns, so whoever moved first
+//! follows from the player to move and the number of pieces.
+
+use crate::game::{Game, Player, PositionError, Rules, Symbol};
+use std::error::Error;
+use std::fmt;
+
+pub fn to_string(game: &Game) -> String {
+    let rules = game.rules();
+
+    let rows: Vec<String> = game
+        .board()
+        .chunks(rules.width())
+        .map(|row| row.iter().map(|square| symbol_char(*square)).collect())
+        .collect();
+
+    format!(
+        "{}x{}:{} {} {} {} {}",
+        rules.width(),
+        rules.height(),
+        rules.win_length(),
+        rows.join("/"),
+        symbol_char(game.current_player().into()),
+        move_list(game.history().iter().copied()),
+        move_list(game.redo_moves()),
+    )
+}
+
+pub fn parse(text: &str) -> Result<Game, NotationError> {
+    let mut fields = text.split_whitespace();
+    let mut next_field = |name| fields.next().ok_or(NotationError::MissingField(name));
+
+    let rules = parse_rules(next_field("rules")?)?;
+    let board = parse_board(next_field("board")?, rules)?;
+    let to_move = parse_player(next_field("player to move")?)?;
+    let history = parse_moves(next_field("history")?)?;
+    let redo = parse_moves(next_field("redo")?)?;
+
+    if let Some(extra) = fields.next() {
+        return Err(NotationError::UnexpectedField(extra.to_string()));
+    }
+
+    // Taking the history off the board gives the position the game started
+    // from, which is usually empty but may have been set up with
+    // `Game::from_board`.
+    let pieces = board
+        .iter()
+        .filter(|square| **square != Symbol::Empty)
+        .count();
+    let first = if pieces % 2 == 0 {
+        to_move
+    } else {
+        to_move.other()
+    };
+
+    let mut starting_board = board.clone();
+    for index in history.iter() {
+        match starting_board.get_mut(*index) {
+            Some(square) if *square != Symbol::Empty => *square = Symbol::Empty,
+            _ => return Err(NotationError::IllegalMove(*index)),
+        }
+    }
+    let mut game = Game::from_board_starting_with(rules, starting_board, first)
+        .map_err(NotationError::InvalidPosition)?;
+
+    // Replaying the moves checks they're legal, then the redo moves are
+    // played and taken back to rebuild the redo list.
+    for index in history.iter().chain(redo.iter()) {
+        game.play(*index)
+            .map_err(|_| NotationError::IllegalMove(*index))?;
+    }
+    for _ in redo.iter() {
+        game.undo();
+    }
+
+    if game.board() != board.as_slice() || game.current_player() != to_move {
+        return Err(NotationError::PositionMismatch);
+    }
+
+    Ok(game)
+}
+
+/// Parses just the rules field, e.g. `3x3:3`.
+pub fn parse_rules(field: &str) -> Result<Rules, NotationError> {
+    let invalid = || NotationError::InvalidRules(field.to_string());
+
+    let (size, win_length) = field.split_once(':').ok_or_else(invalid)?;
+    let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+
+    let width = width.parse().map_err(|_| invalid())?;
+    let height = height.parse().map_err(|_| invalid())?;
+    let win_length = win_length.parse().map_err(|_| invalid())?;
+
+    Rules::new(width, height, win_length).map_err(|_| invalid())
+}
+
+fn parse_board(field: &str, rules: Rules) -> Result<Vec<Symbol>, NotationError> {
+    let board = field
+        .chars()
+        .filter(|c| *c != '/')
+        .map(|c| match c {
+            'X' => Ok(Symbol::Cross),
+            'O' => Ok(Symbol::Naught),
+            '.' => Ok(Symbol::Empty),
+            _ => Err(NotationError::InvalidBoard(field.to_string())),
+        })
+        .collect::<Result<Vec<Symbol>, NotationError>>()?;
+
+    // Checked row by row, as the right number of squares split into the
+    // wrong rows would be read as a different position.
+    let rows: Vec<&str> = field.split('/').collect();
+    if rows.len() != rules.height() || rows.iter().any(|row| row.len() != rules.width()) {
+        return Err(NotationError::BoardDoesNotFit {
+            board: field.to_string(),
+            width: rules.width(),
+            height: rules.height(),
+        });
+    }
+
+    Ok(board)
+}
+
+fn parse_player(field: &str) -> Result<Player, NotationError> {
+    match field {
+        "X" => Ok(Player::Cross),
+        "O" => Ok(Player::Naught),
+        _ => Err(NotationError::InvalidPlayer(field.to_string())),
+    }
+}
+
+fn parse_moves(field: &str) -> Result<Vec<usize>, NotationError> {
+    if field == "-" {
+        return Ok(Vec::new());
+    }
+
+    field
+        .split(',')
+        .map(|index| {
+            index
+                .parse()
+                .map_err(|_| NotationError::InvalidMove(index.to_string()))
+        })
+        .collect()
+}
+
+fn move_list<I: Iterator<Item = usize>>(moves: I) -> String {
+    let moves: Vec<String> = moves.map(|index| index.to_string()).collect();
+
+    if moves.is_empty() {
+        return String::from("-");
+    }
+
+    moves.join(",")
+}
+
+fn symbol_char(symbol: Symbol) -> char {
+    match symbol {
+        Symbol::Cross => 'X',
+        Symbol::Naught => 'O',
+        Symbol::Empty => '.',
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum NotationError {
+    MissingField(&'static str),
+    UnexpectedField(String),
+    InvalidRules(String),
+    InvalidBoard(String),
+    /// The board doesn't have `height` rows of `width` squares.
+    BoardDoesNotFit {
+        board: String,
+        width: usize,
+        height: usize,
+    },
+    InvalidPlayer(String),
+    InvalidMove(String),
+    IllegalMove(usize),
+    InvalidPosition(PositionError),
+    PositionMismatch,
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotationError::MissingField(name) => write!(f, "missing the {}", name),
+            NotationError::UnexpectedField(field) => {
+                write!(f, "unexpected '{}' after the redo moves", field)
+            }
+            NotationError::InvalidRules(field) => write!(
+                f,
+                "'{}' is not a valid set of rules, expected e.g. 3x3:3",
+                field
+            ),
+            NotationError::InvalidBoard(field) => write!(
+                f,
+                "'{}' is not a valid board, expected rows of X, O and .",
+                field
+            ),
+            NotationError::BoardDoesNotFit {
+                board,
+                width,
+                height,
+            } => write!(
+                f,
+                "'{}' doesn't fit the board, expected {} rows of {} squares",
+                board, height, width
+            ),
+            NotationError::InvalidPlayer(field) => {
+                write!(f, "'{}' is not a player, expected X or O", field)
+            }
+            NotationError::InvalidMove(field) => write!(f, "'{}' is not a square", field),
+            NotationError::IllegalMove(index) => {
+                write!(f, "square {} can't be played at that point", index)
+            }
+            NotationError::InvalidPosition(error) => {
+                write!(f, "the starting position is impossible: {}", error)
+            }
+            NotationError::PositionMismatch => {
+                write!(f, "the board and player to move don't match the moves")
+            }
+        }
+    }
+}
+
+impl Error for NotationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NotationError::InvalidPosition(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{play_moves, play_on};
+    use rstest::rstest;
+    use std::collections::HashSet;
+
+    #[test]
+    fn new_game_is_written_with_empty_move_lists() {
+        assert_eq!(to_string(&Game::start()), "3x3:3 .../.../... X - -");
+    }
+
+    #[test]
+    fn board_history_and_redo_are_written() {
+        let mut game = play_moves(&[0, 4, 8]);
+        game.undo();
+
+        assert_eq!(to_string(&game), "3x3:3 X../.O./... X 0,4 8");
+    }
+
+    #[test]
+    fn custom_rules_are_written() {
+        let mut game = Game::with_rules(4, 2, 3).unwrap();
+        game.play(5).ok();
+
+        assert_eq!(to_string(&game), "4x2:3 ..../.X.. O 5 -");
+    }
+
+    #[test]
+    fn redo_moves_are_restored_in_order() {
+        let mut game = play_moves(&[0, 4, 8, 2]);
+        game.undo();
+        game.undo();
+
+        let mut parsed = parse(&to_string(&game)).unwrap();
+
+        assert_eq!(parsed.redo(), Some(8));
+        assert_eq!(parsed.redo(), Some(2));
+    }
+
+    // Walks every position that can follow from `game`, finished ones
+    // included, round tripping each along with a version that has its last
+    // move undone.
+    fn assert_round_trips(game: &Game, seen: &mut HashSet<Vec<Symbol>>) {
+        if !seen.insert(game.board().to_vec()) {
+            return;
+        }
+
+        assert_eq!(&parse(&to_string(game)).unwrap(), game);
+
+        let mut undone = game.clone();
+        if undone.undo().is_some() {
+            assert_eq!(parse(&to_string(&undone)).unwrap(), undone);
+        }
+
+        for index in game.available_moves() {
+            let mut next = game.clone();
+            if next.play(index).is_ok() {
+                assert_round_trips(&next, seen);
+            }
+        }
+    }
+
+    #[test]
+    fn reachable_positions_round_trip() {
+        let mut seen = HashSet::new();
+        assert_round_trips(&Game::start(), &mut seen);
+
+        assert_eq!(seen.len(), 5478);
+    }
+
+    #[test]
+    fn gomoku_positions_round_trip() {
+        let mut game = Game::with_rules(15, 15, 5).unwrap();
+        play_on(&mut game, &[112, 113, 97, 127, 82, 67]);
+        game.undo();
+
+        assert_eq!(parse(&to_string(&game)).unwrap(), game);
+    }
+
+    #[test]
+    fn missing_fields_are_reported() {
+        assert_eq!(
+            parse("3x3:3 .../.../..."),
+            Err(NotationError::MissingField("player to move"))
+        );
+        assert_eq!(parse(""), Err(NotationError::MissingField("rules")));
+    }
+
+    #[test]
+    fn extra_fields_are_reported() {
+        assert_eq!(
+            parse("3x3:3 .../.../... X - - extra"),
+            Err(NotationError::UnexpectedField(String::from("extra")))
+        );
+    }
+
+    #[test]
+    fn malformed_fields_are_reported() {
+        assert_eq!(
+            parse("3by3 .../.../... X - -"),
+            Err(NotationError::InvalidRules(String::from("3by3")))
+        );
+        assert_eq!(
+            parse("3x3:4 .../.../... X - -"),
+            Err(NotationError::InvalidRules(String::from("3x3:4")))
+        );
+        assert_eq!(
+            parse("3x3:3 ..Z/.../... X - -"),
+            Err(NotationError::InvalidBoard(String::from("..Z/.../...")))
+        );
+        assert_eq!(
+            parse("3x3:3 .../.../... Y - -"),
+            Err(NotationError::InvalidPlayer(String::from("Y")))
+        );
+        assert_eq!(
+            parse("3x3:3 X../.../... O 0,a -"),
+            Err(NotationError::InvalidMove(String::from("a")))
+        );
+    }
+
+    #[rstest]
+    #[case("XO/.X../...")]
+    #[case("XO./.X./.../...")]
+    #[case(".........")]
+    fn boards_must_have_the_right_rows(#[case] board: &str) {
+        assert_eq!(
+            parse(&format!("3x3:3 {} O - -", board)),
+            Err(NotationError::BoardDoesNotFit {
+                board: board.to_string(),
+                width: 3,
+                height: 3
+            })
+        );
+    }
+
+    #[test]
+    fn illegal_moves_are_reported() {
+        assert_eq!(
+            parse("3x3:3 X../.../... O 0,0 -"),
+            Err(NotationError::IllegalMove(0))
+        );
+        assert_eq!(
+            parse("3x3:3 X../.../... O 9 -"),
+            Err(NotationError::IllegalMove(9))
+        );
+    }
+
+    #[test]
+    fn games_set_up_from_a_position_round_trip() {
+        let mut game: Game = "X...O....".parse().unwrap();
+        game.play(8).ok();
+        game.play(2).ok();
+        game.undo();
+
+        assert_eq!(to_string(&game), "3x3:3 X../.O./..X O 8 2");
+        assert_eq!(parse(&to_string(&game)).unwrap(), game);
+    }
+
+    #[test]
+    fn games_naughts_started_round_trip() {
+        let mut game = Game::starting_with(Rules::classic(), Player::Naught);
+        assert_eq!(to_string(&game), "3x3:3 .../.../... O - -");
+        assert_eq!(parse(&to_string(&game)).unwrap(), game);
+
+        game.play(4).ok();
+        game.play(0).ok();
+        game.undo();
+
+        assert_eq!(to_string(&game), "3x3:3 .../.O./... X 4 0");
+        assert_eq!(parse(&to_string(&game)).unwrap(), game);
+    }
+
+    #[test]
+    fn starting_position_must_be_possible() {
+        assert_eq!(
+            parse("3x3:3 XX./O../... X 3 -"),
+            Err(NotationError::InvalidPosition(PositionError::PieceCounts {
+                crosses: 2,
+                naughts: 0
+            }))
+        );
+        assert_eq!(
+            parse("3x3:3 .../.../... X 0 -"),
+            Err(NotationError::IllegalMove(0))
+        );
+    }
+
+    #[test]
+    fn board_must_match_the_moves() {
+        assert_eq!(
+            parse("3x3:3 X../.../... X 0 -"),
+            Err(NotationError::PositionMismatch)
+        );
+        assert_eq!(
+            parse("3x3:3 X../.../... O 0 0"),
+            Err(NotationError::IllegalMove(0))
+        );
+    }
+}
@@ -0,0 +1,344 @@
+//! Steps through a finished game one move at a time, with the solver's
+//! verdict on each move.
+
+use crate::analysis::{self, Evaluation, Solver, Verdict};
+use crate::coord::{self, SquareError};
+use crate::game::{Game, PlayError, Player, Rules};
+use crate::notation::{self, NotationError};
+use std::error::Error;
+use std::fmt;
+
+/// How a move compares with the best one available.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Quality {
+    /// Keeps the best result the position allowed.
+    Best,
+    /// Gives up a win but still draws.
+    Inaccuracy,
+    /// Turns a win or a draw into a loss.
+    Blunder,
+}
+
+impl fmt::Display for Quality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            Quality::Best => "best",
+            Quality::Inaccuracy => "inaccuracy",
+            Quality::Blunder => "blunder",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Annotation {
+    pub player: Player,
+    pub quality: Quality,
+    /// The result after the move played, for the player who played it.
+    pub played: Evaluation,
+    /// The result the best move would have kept.
+    pub best: Evaluation,
+    pub best_move: usize,
+}
+
+/// A game rewound to its start, with navigation through its moves using the
+/// game's own undo and redo.
+pub struct Replay {
+    game: Game,
+    moves: usize,
+    /// One per move, `None` for positions too big to solve.
+    annotations: Vec<Option<Annotation>>,
+}
+
+impl Replay {
+    /// Annotates every move of `game` and rewinds it to before the first
+    /// move. Moves waiting to be redone in `game` aren't part of the replay.
+    pub fn new(mut game: Game) -> Replay {
+        let moves = game.history().len();
+        while game.undo().is_some() {}
+
+        let mut solver = Solver::new();
+        let mut annotations = Vec::new();
+        for _ in 0..moves {
+            let before = game.clone();
+            let square = match game.redo() {
+                Some(square) => square,
+                None => break,
+            };
+            annotations.push(annotate(&mut solver, &before, square));
+        }
+        while game.undo().is_some() {}
+
+        Replay {
+            game,
+            moves,
+            annotations,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// How many moves have been played so far.
+    pub fn position(&self) -> usize {
+        self.game.history().len()
+    }
+
+    pub fn moves(&self) -> usize {
+        self.moves
+    }
+
+    /// Plays the next move, returning false at the end of the game.
+    pub fn forward(&mut self) -> bool {
+        self.position() < self.moves && self.game.redo().is_some()
+    }
+
+    /// Takes back the last move, returning false at the start of the game.
+    pub fn back(&mut self) -> bool {
+        self.game.undo().is_some()
+    }
+
+    pub fn rewind(&mut self) {
+        while self.back() {}
+    }
+
+    pub fn fast_forward(&mut self) {
+        while self.forward() {}
+    }
+
+    /// The last move played, along with its annotation if the position
+    /// could be solved.
+    pub fn last_move(&self) -> Option<(usize, Option<Annotation>)> {
+        let square = *self.game.history().last()?;
+        let annotation = self.annotations.get(self.position() - 1).copied().flatten();
+
+        Some((square, annotation))
+    }
+}
+
+/// Reads a game in the save file notation, or as the moves played from an
+/// empty board on `rules`, optionally in the form `Moves played: 4, 0, 8`.
+pub fn read_game(text: &str, rules: Rules) -> Result<Game, RecordingError> {
+    let is_notation = text.split_whitespace().next().is_some_and(|field| {
+        field.contains(':') && field.starts_with(|c: char| c.is_ascii_digit())
+    });
+    if is_notation {
+        return notation::parse(text).map_err(RecordingError::Notation);
+    }
+
+    let moves = text.strip_prefix("Moves played:").unwrap_or(text);
+    let mut game = Game::starting_with(rules, Player::Cross);
+    for square in moves.split(|c: char| c == ',' || c.is_whitespace()) {
+        if square.is_empty() {
+            continue;
+        }
+
+        let index = coord::parse_square(square, rules).map_err(RecordingError::Square)?;
+        if let Err(error) = game.play(index) {
+            return Err(RecordingError::IllegalMove {
+                number: game.history().len() + 1,
+                square: square.to_string(),
+                error,
+            });
+        }
+    }
+
+    Ok(game)
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum RecordingError {
+    Notation(NotationError),
+    Square(SquareError),
+    /// Move `number`, counting from 1, was `square` as written.
+    IllegalMove {
+        number: usize,
+        square: String,
+        error: PlayError,
+    },
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecordingError::Notation(error) => write!(f, "{}", error),
+            RecordingError::Square(error) => write!(f, "{}", error),
+            RecordingError::IllegalMove {
+                number,
+                square,
+                error,
+            } => write!(f, "move {} ({}) can't be played, {}", number, square, error),
+        }
+    }
+}
+
+impl Error for RecordingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RecordingError::Notation(error) => Some(error),
+            RecordingError::Square(error) => Some(error),
+            RecordingError::IllegalMove { error, .. } => Some(error),
+        }
+    }
+}
+
+fn annotate(solver: &mut Solver, before: &Game, square: usize) -> Option<Annotation> {
+    if before.available_moves().len() > analysis::MAX_EMPTY_SQUARES {
+        return None;
+    }
+
+    let best = solver.evaluate(before);
+    let played = solver.evaluate_move(before, square)?;
+    let best_move = solver.best_move(before)?;
+
+    let quality = match (best.verdict, played.verdict) {
+        (best, played) if best == played => Quality::Best,
+        (_, Verdict::Loss) => Quality::Blunder,
+        _ => Quality::Inaccuracy,
+    };
+
+    Some(Annotation {
+        player: before.current_player(),
+        quality,
+        played,
+        best,
+        best_move,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Rules;
+    use crate::testing::{play_moves, play_on};
+    use rstest::rstest;
+
+    fn replay(moves: &[usize]) -> Replay {
+        Replay::new(play_moves(moves))
+    }
+
+    fn quality_of(replay: &mut Replay, move_number: usize) -> Quality {
+        replay.rewind();
+        for _ in 0..move_number {
+            replay.forward();
+        }
+        replay.last_move().unwrap().1.unwrap().quality
+    }
+
+    #[test]
+    fn replays_start_from_the_beginning() {
+        let replay = replay(&[4, 0, 8]);
+
+        assert_eq!(replay.position(), 0);
+        assert_eq!(replay.moves(), 3);
+        assert_eq!(replay.game().board(), Game::start().board());
+        assert_eq!(replay.last_move(), None);
+    }
+
+    #[test]
+    fn moves_can_be_stepped_through_both_ways() {
+        let mut replay = replay(&[4, 0, 8]);
+
+        assert!(replay.forward());
+        assert!(replay.forward());
+        assert_eq!(replay.last_move().map(|(square, _)| square), Some(0));
+
+        assert!(replay.back());
+        assert_eq!(replay.position(), 1);
+
+        replay.fast_forward();
+        assert_eq!(replay.position(), 3);
+        assert!(!replay.forward());
+
+        replay.rewind();
+        assert!(!replay.back());
+    }
+
+    #[test]
+    fn moves_waiting_to_be_redone_are_left_out() {
+        let mut game = Game::start();
+        play_on(&mut game, &[4, 0, 8]);
+        game.undo();
+
+        let mut replay = Replay::new(game);
+        replay.fast_forward();
+
+        assert_eq!(replay.position(), 2);
+    }
+
+    #[test]
+    fn moves_are_graded_against_the_best_move() {
+        // Answering the centre on an edge loses for O, and X's reply of 7
+        // lets O off with a draw where any other move still wins.
+        let mut replay = replay(&[4, 1, 7]);
+
+        assert_eq!(quality_of(&mut replay, 1), Quality::Best);
+        assert_eq!(quality_of(&mut replay, 2), Quality::Blunder);
+        assert_eq!(quality_of(&mut replay, 3), Quality::Inaccuracy);
+    }
+
+    #[test]
+    fn annotations_describe_the_alternatives() {
+        let mut replay = replay(&[4, 1]);
+        replay.fast_forward();
+
+        let (square, annotation) = replay.last_move().unwrap();
+        let annotation = annotation.unwrap();
+        assert_eq!(square, 1);
+        assert_eq!(annotation.player, Player::Naught);
+        assert_eq!(annotation.played.verdict, Verdict::Loss);
+        assert_eq!(annotation.best.verdict, Verdict::Draw);
+        assert_eq!(annotation.best_move, 0);
+    }
+
+    #[test]
+    fn big_boards_are_replayed_without_annotations() {
+        let mut game = Game::with_rules(4, 4, 3).unwrap();
+        game.play(0).ok();
+
+        let mut replay = Replay::new(game);
+        replay.forward();
+
+        assert_eq!(replay.last_move(), Some((0, None)));
+        assert_eq!(replay.game().rules(), Rules::new(4, 4, 3).unwrap());
+    }
+
+    #[rstest]
+    #[case("4, 0, 8")]
+    #[case("Moves played: 4, 0, 8")]
+    #[case("B2 A1\nC3")]
+    fn recorded_moves_are_played_in_order(#[case] text: &str) {
+        let game = read_game(text, Rules::classic()).unwrap();
+
+        assert_eq!(game.history(), &[4, 0, 8]);
+    }
+
+    #[test]
+    fn recorded_games_can_be_in_the_save_notation() {
+        let game = play_moves(&[4, 0, 8]);
+
+        let read = read_game(&notation::to_string(&game), Rules::new(4, 4, 3).unwrap());
+
+        assert_eq!(read, Ok(game));
+    }
+
+    #[test]
+    fn unplayable_recorded_moves_are_reported() {
+        assert_eq!(
+            read_game("4, 4", Rules::classic()),
+            Err(RecordingError::IllegalMove {
+                number: 2,
+                square: String::from("4"),
+                error: PlayError::SquareTaken {
+                    index: 4,
+                    by: Player::Cross
+                },
+            })
+        );
+        assert!(matches!(
+            read_game("4, D1", Rules::classic()),
+            Err(RecordingError::Square(SquareError::OffBoard(_)))
+        ));
+    }
+}
@@ -0,0 +1,206 @@
+//! A best of N series of games, with the players taking turns to move first.
+
+use crate::game::{Game, GameStatus, Player, Rules};
+
+#[derive(Default, PartialEq, Debug)]
+pub struct Score {
+    pub crosses: u32,
+    pub naughts: u32,
+    pub draws: u32,
+}
+
+impl Score {
+    /// Adds the result of a finished game. Games still in progress are
+    /// ignored.
+    pub fn record(&mut self, status: &GameStatus) {
+        match status {
+            GameStatus::Won {
+                by: Player::Cross, ..
+            } => self.crosses += 1,
+            GameStatus::Won {
+                by: Player::Naught, ..
+            } => self.naughts += 1,
+            GameStatus::Draw => self.draws += 1,
+            _ => {}
+        }
+    }
+
+    pub fn played(&self) -> u32 {
+        self.crosses + self.naughts + self.draws
+    }
+}
+
+pub struct Match {
+    rules: Rules,
+    games: u32,
+    score: Score,
+}
+
+impl Match {
+    /// A series of up to `games` games, at least one. Cross moves first in
+    /// the opening game.
+    pub fn best_of(rules: Rules, games: u32) -> Match {
+        Match {
+            rules,
+            games: games.max(1),
+            score: Score::default(),
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.games
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    /// The next game to play, or `None` once the match is over.
+    pub fn next_game(&self) -> Option<Game> {
+        if self.is_over() {
+            return None;
+        }
+
+        let first = if self.score.played().is_multiple_of(2) {
+            Player::Cross
+        } else {
+            Player::Naught
+        };
+
+        Some(Game::starting_with(self.rules, first))
+    }
+
+    pub fn record(&mut self, game: &Game) {
+        self.score.record(&game.status());
+    }
+
+    /// The match ends when every game has been played or one player is
+    /// further ahead than the games left could make up.
+    pub fn is_over(&self) -> bool {
+        let remaining = self.games.saturating_sub(self.score.played());
+        let lead = self.score.crosses.abs_diff(self.score.naughts);
+
+        remaining == 0 || lead > remaining
+    }
+
+    /// The player who won the most games once the match is over, or `None`
+    /// if it's still going or ended level.
+    pub fn winner(&self) -> Option<Player> {
+        if !self.is_over() || self.score.crosses == self.score.naughts {
+            return None;
+        }
+
+        if self.score.crosses > self.score.naughts {
+            Some(Player::Cross)
+        } else {
+            Some(Player::Naught)
+        }
+    }
+
+    pub fn scoreboard(&self) -> String {
+        format!(
+            "Score after game {} of {}: X {}, O {}, draws {}",
+            self.score.played(),
+            self.games,
+            self.score.crosses,
+            self.score.naughts,
+            self.score.draws
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::play_on;
+    use rstest::rstest;
+
+    // Whoever moves first takes the top row.
+    const FIRST_WINS: &[usize] = &[0, 3, 1, 4, 2];
+    const DRAW: &[usize] = &[0, 1, 2, 4, 3, 5, 7, 6, 8];
+
+    fn play_series(games: u32, results: &[&[usize]]) -> Match {
+        let mut series = Match::best_of(Rules::classic(), games);
+        for moves in results {
+            let mut game = series.next_game().unwrap();
+            play_on(&mut game, moves);
+            series.record(&game);
+        }
+        series
+    }
+
+    #[test]
+    fn players_take_turns_to_move_first() {
+        let mut series = Match::best_of(Rules::classic(), 3);
+
+        let mut first = series.next_game().unwrap();
+        assert_eq!(first.current_player(), Player::Cross);
+        play_on(&mut first, DRAW);
+        series.record(&first);
+
+        let second = series.next_game().unwrap();
+        assert_eq!(second.current_player(), Player::Naught);
+    }
+
+    #[test]
+    fn results_are_counted() {
+        let series = play_series(5, &[FIRST_WINS, FIRST_WINS, DRAW]);
+
+        assert_eq!(
+            series.score(),
+            &Score {
+                crosses: 1,
+                naughts: 1,
+                draws: 1
+            }
+        );
+        assert_eq!(
+            series.scoreboard(),
+            "Score after game 3 of 5: X 1, O 1, draws 1"
+        );
+    }
+
+    #[test]
+    fn unfinished_games_are_not_counted() {
+        let mut series = Match::best_of(Rules::classic(), 3);
+        series.record(&Game::start());
+
+        assert_eq!(series.score(), &Score::default());
+    }
+
+    #[rstest]
+    #[case(1, &[DRAW], true, None)]
+    #[case(3, &[FIRST_WINS], false, None)]
+    #[case(3, &[FIRST_WINS, DRAW], false, None)]
+    #[case(3, &[FIRST_WINS, DRAW, DRAW], true, Some(Player::Cross))]
+    #[case(3, &[DRAW, FIRST_WINS, FIRST_WINS], true, None)]
+    #[case(3, &[FIRST_WINS, FIRST_WINS, FIRST_WINS], true, Some(Player::Cross))]
+    #[case(2, &[FIRST_WINS, FIRST_WINS], true, None)]
+    fn matches_end_once_decided(
+        #[case] games: u32,
+        #[case] results: &[&[usize]],
+        #[case] over: bool,
+        #[case] winner: Option<Player>,
+    ) {
+        let series = play_series(games, results);
+
+        assert_eq!(series.is_over(), over);
+        assert_eq!(series.winner(), winner);
+        assert_eq!(series.next_game().is_none(), over);
+    }
+
+    #[test]
+    fn a_clear_lead_ends_the_match_early() {
+        // X wins as the first player, then O's first move is off to the side
+        // and X wins again.
+        let series = play_series(3, &[FIRST_WINS, &[6, 0, 7, 1, 3, 2]]);
+
+        assert!(series.is_over());
+        assert_eq!(series.winner(), Some(Player::Cross));
+    }
+
+    #[test]
+    fn at_least_one_game_is_played() {
+        assert_eq!(Match::best_of(Rules::classic(), 0).games(), 1);
+    }
+}
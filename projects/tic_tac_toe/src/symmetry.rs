@@ -0,0 +1,335 @@
+//! Rotations and reflections of the board. Positions that are rotations or
+//! reflections of each other play the same way, so they can share one
+//! canonical form.
+
+use crate::game::{Game, Rules, Symbol};
+
+/// One of the ways of turning or flipping the board onto itself.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Transform {
+    Identity,
+    /// A quarter turn clockwise.
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Swaps the left and right edges.
+    FlipHorizontal,
+    /// Swaps the top and bottom edges.
+    FlipVertical,
+    /// Reflects in the diagonal from the top left corner.
+    FlipDiagonal,
+    /// Reflects in the diagonal from the top right corner.
+    FlipAntiDiagonal,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::FlipHorizontal,
+        Transform::FlipVertical,
+        Transform::FlipDiagonal,
+        Transform::FlipAntiDiagonal,
+    ];
+
+    /// The transforms that keep the board's shape. Quarter turns and the
+    /// diagonal flips only fit square boards.
+    pub fn for_rules(rules: Rules) -> Vec<Transform> {
+        Transform::ALL
+            .iter()
+            .copied()
+            .filter(|transform| transform.fits(rules))
+            .collect()
+    }
+
+    fn fits(self, rules: Rules) -> bool {
+        rules.width() == rules.height() || !self.swaps_axes()
+    }
+
+    fn swaps_axes(self) -> bool {
+        matches!(
+            self,
+            Transform::Rotate90
+                | Transform::Rotate270
+                | Transform::FlipDiagonal
+                | Transform::FlipAntiDiagonal
+        )
+    }
+
+    /// The transform that undoes this one.
+    pub fn inverse(self) -> Transform {
+        match self {
+            Transform::Rotate90 => Transform::Rotate270,
+            Transform::Rotate270 => Transform::Rotate90,
+            other => other,
+        }
+    }
+
+    /// Where the square at `index` ends up, or `None` if it's off the board
+    /// or the transform doesn't fit the board's shape.
+    pub fn apply(self, index: usize, rules: Rules) -> Option<usize> {
+        if index >= rules.squares() || !self.fits(rules) {
+            return None;
+        }
+
+        let (width, height) = (rules.width(), rules.height());
+        let (row, col) = (index / width, index % width);
+        let (last_row, last_col) = (height - 1, width - 1);
+
+        // The axis swapping transforms only fit square boards, where the last
+        // row and column are the same.
+        let (row, col) = match self {
+            Transform::Identity => (row, col),
+            Transform::Rotate90 => (col, last_row - row),
+            Transform::Rotate180 => (last_row - row, last_col - col),
+            Transform::Rotate270 => (last_col - col, row),
+            Transform::FlipHorizontal => (row, last_col - col),
+            Transform::FlipVertical => (last_row - row, col),
+            Transform::FlipDiagonal => (col, row),
+            Transform::FlipAntiDiagonal => (last_col - col, last_row - row),
+        };
+
+        Some(row * width + col)
+    }
+
+    /// The whole board moved, or `None` if it isn't the size `rules` give or
+    /// the transform doesn't fit its shape.
+    pub fn apply_to_board(self, board: &[Symbol], rules: Rules) -> Option<Vec<Symbol>> {
+        if board.len() != rules.squares() {
+            return None;
+        }
+
+        let mut transformed = vec![Symbol::Empty; board.len()];
+        for (index, symbol) in board.iter().enumerate() {
+            transformed[self.apply(index, rules)?] = *symbol;
+        }
+        Some(transformed)
+    }
+}
+
+/// A board in canonical form, along with the transform that took the
+/// original board there.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Canonical {
+    pub board: Vec<Symbol>,
+    pub transform: Transform,
+    rules: Rules,
+}
+
+impl Canonical {
+    /// Picks whichever rotation or reflection of the board comes first in
+    /// base 3 order, so every board in the same family gets the same one.
+    pub fn of(game: &Game) -> Canonical {
+        let rules = game.rules();
+
+        Transform::for_rules(rules)
+            .into_iter()
+            .filter_map(|transform| {
+                Some(Canonical {
+                    board: transform.apply_to_board(game.board(), rules)?,
+                    transform,
+                    rules,
+                })
+            })
+            .min_by(|a, b| digits(&a.board).cmp(&digits(&b.board)))
+            .unwrap_or(Canonical {
+                board: game.board().to_vec(),
+                transform: Transform::Identity,
+                rules,
+            })
+    }
+
+    /// Where a move in the original position lands in the canonical one,
+    /// `None` if it's off the board.
+    pub fn move_to_canonical(&self, index: usize) -> Option<usize> {
+        self.transform.apply(index, self.rules)
+    }
+
+    /// Where a move in the canonical position lands in the original one,
+    /// `None` if it's off the board.
+    pub fn move_from_canonical(&self, index: usize) -> Option<usize> {
+        self.transform.inverse().apply(index, self.rules)
+    }
+
+    pub fn hash(&self) -> Option<u64> {
+        encode(&self.board)
+    }
+}
+
+/// A hash that's the same for every rotation and reflection of the board.
+/// `None` for boards too big to encode, see `encode`.
+pub fn canonical_hash(game: &Game) -> Option<u64> {
+    Canonical::of(game).hash()
+}
+
+/// Encodes a board as a base 3 number, the first square being the most
+/// significant digit. Boards of more than 40 squares don't fit in a `u64`.
+pub fn encode(board: &[Symbol]) -> Option<u64> {
+    if board.len() > 40 {
+        return None;
+    }
+
+    Some(
+        digits(board)
+            .iter()
+            .fold(0, |hash, digit| hash * 3 + u64::from(*digit)),
+    )
+}
+
+fn digits(board: &[Symbol]) -> Vec<u8> {
+    board
+        .iter()
+        .map(|symbol| match symbol {
+            Symbol::Empty => 0,
+            Symbol::Cross => 1,
+            Symbol::Naught => 2,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::play_moves;
+    use rstest::rstest;
+    use std::collections::HashSet;
+
+    fn collect_positions(game: &Game, boards: &mut HashSet<Vec<Symbol>>) {
+        if !boards.insert(game.board().to_vec()) {
+            return;
+        }
+
+        for index in game.available_moves() {
+            let mut next = game.clone();
+            if next.play(index).is_ok() {
+                collect_positions(&next, boards);
+            }
+        }
+    }
+
+    fn reachable_positions() -> Vec<Game> {
+        let mut boards = HashSet::new();
+        collect_positions(&Game::start(), &mut boards);
+
+        boards
+            .into_iter()
+            .map(|board| Game::from_board(Rules::classic(), board).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn reachable_positions_are_counted_up_to_symmetry() {
+        let positions = reachable_positions();
+        let canonical: HashSet<u64> = positions
+            .iter()
+            .map(|game| canonical_hash(game).unwrap())
+            .collect();
+
+        assert_eq!(positions.len(), 5478);
+        assert_eq!(canonical.len(), 765);
+    }
+
+    #[rstest]
+    // The corners turn clockwise.
+    #[case(Transform::Rotate90, [2, 5, 8, 1, 4, 7, 0, 3, 6])]
+    #[case(Transform::Rotate180, [8, 7, 6, 5, 4, 3, 2, 1, 0])]
+    #[case(Transform::Rotate270, [6, 3, 0, 7, 4, 1, 8, 5, 2])]
+    #[case(Transform::FlipHorizontal, [2, 1, 0, 5, 4, 3, 8, 7, 6])]
+    #[case(Transform::FlipVertical, [6, 7, 8, 3, 4, 5, 0, 1, 2])]
+    #[case(Transform::FlipDiagonal, [0, 3, 6, 1, 4, 7, 2, 5, 8])]
+    #[case(Transform::FlipAntiDiagonal, [8, 5, 2, 7, 4, 1, 6, 3, 0])]
+    fn transforms_move_squares(#[case] transform: Transform, #[case] expected: [usize; 9]) {
+        let moved: Vec<usize> = (0..9)
+            .map(|index| transform.apply(index, Rules::classic()).unwrap())
+            .collect();
+
+        assert_eq!(moved, expected);
+    }
+
+    #[test]
+    fn inverses_undo_each_transform() {
+        let rules = Rules::new(4, 4, 3).unwrap();
+
+        for transform in Transform::ALL.iter() {
+            for index in 0..16 {
+                let there = transform.apply(index, rules).unwrap();
+                assert_eq!(transform.inverse().apply(there, rules), Some(index));
+            }
+        }
+    }
+
+    #[test]
+    fn rectangular_boards_only_flip_and_half_turn() {
+        let rules = Rules::new(4, 2, 3).unwrap();
+
+        assert_eq!(
+            Transform::for_rules(rules),
+            [
+                Transform::Identity,
+                Transform::Rotate180,
+                Transform::FlipHorizontal,
+                Transform::FlipVertical
+            ]
+        );
+        assert_eq!(Transform::for_rules(Rules::classic()).len(), 8);
+        assert_eq!(Transform::FlipVertical.apply(1, rules), Some(5));
+    }
+
+    #[test]
+    fn squares_and_boards_that_do_not_fit_are_not_moved() {
+        let rectangle = Rules::new(4, 2, 3).unwrap();
+
+        assert_eq!(Transform::FlipVertical.apply(9, Rules::classic()), None);
+        assert_eq!(Transform::Rotate90.apply(0, rectangle), None);
+        assert_eq!(
+            Transform::FlipDiagonal.apply_to_board(&[Symbol::Empty; 8], rectangle),
+            None
+        );
+        assert_eq!(
+            Transform::Identity.apply_to_board(&[Symbol::Empty; 8], Rules::classic()),
+            None
+        );
+    }
+
+    #[test]
+    fn symmetric_positions_share_a_hash() {
+        // A corner opening from each corner, and the edge openings.
+        let corners: HashSet<u64> = [0, 2, 6, 8]
+            .iter()
+            .map(|index| canonical_hash(&play_moves(&[*index])).unwrap())
+            .collect();
+        let edges: HashSet<u64> = [1, 3, 5, 7]
+            .iter()
+            .map(|index| canonical_hash(&play_moves(&[*index])).unwrap())
+            .collect();
+
+        assert_eq!(corners.len(), 1);
+        assert_eq!(edges.len(), 1);
+        assert_ne!(corners, edges);
+    }
+
+    #[test]
+    fn moves_map_to_and_from_the_canonical_position() {
+        for game in reachable_positions() {
+            let canonical = Canonical::of(&game);
+
+            for index in game.available_moves() {
+                let moved = canonical.move_to_canonical(index).unwrap();
+                assert_eq!(canonical.board[moved], Symbol::Empty);
+                assert_eq!(canonical.move_from_canonical(moved), Some(index));
+            }
+        }
+    }
+
+    #[test]
+    fn boards_are_encoded_in_base_3() {
+        let game: Game = "X../.O./...".parse().unwrap();
+
+        assert_eq!(encode(Game::start().board()), Some(0));
+        assert_eq!(encode(game.board()), Some(3u64.pow(8) + 2 * 3u64.pow(4)));
+        assert_eq!(encode(&[Symbol::Naught; 40]), Some(3u64.pow(40) - 1));
+        assert_eq!(encode(&[Symbol::Empty; 41]), None);
+    }
+}
@@ -0,0 +1,17 @@
+//! Helpers shared by the unit tests.
+
+use crate::game::Game;
+
+/// Plays each move in turn, failing the test if any of them is illegal.
+pub fn play_on(game: &mut Game, moves: &[usize]) {
+    for index in moves {
+        assert!(game.play(*index).is_ok(), "{} should be playable", index);
+    }
+}
+
+/// A classic game after `moves`, which must all be legal.
+pub fn play_moves(moves: &[usize]) -> Game {
+    let mut game = Game::start();
+    play_on(&mut game, moves);
+    game
+}
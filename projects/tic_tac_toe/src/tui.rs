@@ -0,0 +1,356 @@
+//! A full screen mode where players move a cursor around the board with the
+//! arrow keys (or hjkl) and press enter to play.
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::Print;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+use std::io::{self, Write};
+use tic_tac_toe::display::board_lines;
+use tic_tac_toe::game::{Game, GameStatus, PlayError};
+use tic_tac_toe::series::Score;
+
+const HELP: &str = "arrows/hjkl move, enter place, u undo, r redo, n new game, q quit";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Place,
+    Undo,
+    Redo,
+    NewGame,
+    Quit,
+}
+
+pub fn action_for(key: KeyCode) -> Option<Action> {
+    match key {
+        KeyCode::Up | KeyCode::Char('k') => Some(Action::Up),
+        KeyCode::Down | KeyCode::Char('j') => Some(Action::Down),
+        KeyCode::Left | KeyCode::Char('h') => Some(Action::Left),
+        KeyCode::Right | KeyCode::Char('l') => Some(Action::Right),
+        KeyCode::Enter | KeyCode::Char(' ') => Some(Action::Place),
+        KeyCode::Char('u') => Some(Action::Undo),
+        KeyCode::Char('r') => Some(Action::Redo),
+        KeyCode::Char('n') => Some(Action::NewGame),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+        _ => None,
+    }
+}
+
+/// Everything the screen shows, kept apart from the terminal so it can be
+/// driven by tests.
+pub struct Screen {
+    new_game: Game,
+    game: Game,
+    cursor: usize,
+    score: Score,
+    /// Whether the current game's result is in the score, so undoing and
+    /// replaying the last move doesn't count it twice.
+    recorded: bool,
+    message: Option<String>,
+}
+
+impl Screen {
+    pub fn new(game: Game) -> Screen {
+        let cursor = centre(&game);
+        Screen {
+            new_game: game.clone(),
+            game,
+            cursor,
+            score: Score::default(),
+            recorded: false,
+            message: None,
+        }
+    }
+
+    /// Applies an action, returning false once the player wants to quit.
+    pub fn handle(&mut self, action: Action) -> bool {
+        let width = self.game.rules().width();
+        let height = self.game.rules().height();
+        let (row, col) = (self.cursor / width, self.cursor % width);
+        self.message = None;
+
+        match action {
+            Action::Up if row > 0 => self.cursor -= width,
+            Action::Down if row + 1 < height => self.cursor += width,
+            Action::Left if col > 0 => self.cursor -= 1,
+            Action::Right if col + 1 < width => self.cursor += 1,
+            Action::Up | Action::Down | Action::Left | Action::Right => {}
+            Action::Place => self.place(),
+            Action::Undo => {
+                if self.game.undo().is_none() {
+                    self.message = Some(String::from("Nothing to undo"));
+                }
+            }
+            Action::Redo => {
+                if self.game.redo().is_none() {
+                    self.message = Some(String::from("Nothing to redo"));
+                }
+            }
+            Action::NewGame => {
+                self.game = self.new_game.clone();
+                self.cursor = centre(&self.game);
+                self.recorded = false;
+            }
+            Action::Quit => return false,
+        }
+
+        self.record_result();
+        true
+    }
+
+    /// Adds the result to the score the first time the game finishes,
+    /// whether that's by placing or redoing the last move.
+    fn record_result(&mut self) {
+        let status = self.game.status();
+        if !self.recorded && !matches!(status, GameStatus::InProgress { .. }) {
+            self.score.record(&status);
+            self.recorded = true;
+        }
+    }
+
+    fn place(&mut self) {
+        match self.game.play(self.cursor) {
+            Ok(()) => {}
+            Err(PlayError::SquareTaken { .. }) => {
+                self.message = Some(String::from("That square is already taken"))
+            }
+            Err(PlayError::OutOfRange { .. }) => {
+                self.message = Some(String::from("That square is off the board"))
+            }
+            Err(PlayError::GameOver) => {
+                self.message = Some(String::from("The game is over, press n for a new game"))
+            }
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![String::from("Tic Tac Toe"), String::new()];
+        lines.extend(board_lines(&self.game, true, Some(self.cursor)));
+        lines.push(String::new());
+
+        let status = match self.game.status() {
+            GameStatus::InProgress { next } => format!("{} to play", next),
+            GameStatus::Won { by, .. } => format!("{} wins!", by),
+            GameStatus::Draw => String::from("It's a draw!"),
+        };
+        lines.push(format!(
+            "{} | Score X {} O {} Draws {}",
+            status, self.score.crosses, self.score.naughts, self.score.draws
+        ));
+        lines.push(self.message.clone().unwrap_or_default());
+        lines.push(String::from(HELP));
+
+        lines
+    }
+}
+
+fn centre(game: &Game) -> usize {
+    let rules = game.rules();
+    (rules.height() / 2) * rules.width() + rules.width() / 2
+}
+
+/// Puts the terminal back how it was however the screen is left.
+struct RawModeGuard;
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        execute!(io::stdout(), Show, LeaveAlternateScreen).ok();
+        terminal::disable_raw_mode().ok();
+    }
+}
+
+pub fn run(game: Game) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    let _guard = RawModeGuard;
+    execute!(stdout, EnterAlternateScreen, Hide)?;
+
+    let mut screen = Screen::new(game);
+
+    loop {
+        queue!(stdout, Clear(ClearType::All))?;
+        for (row, line) in screen.lines().iter().enumerate() {
+            queue!(stdout, MoveTo(0, row as u16), Print(line))?;
+        }
+        stdout.flush()?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if let Some(action) = action_for(key.code) {
+                if !screen.handle(action) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use tic_tac_toe::game::{Player, Symbol};
+
+    fn screen_after(actions: &[Action]) -> Screen {
+        let mut screen = Screen::new(Game::start());
+        for action in actions {
+            screen.handle(*action);
+        }
+        screen
+    }
+
+    #[rstest]
+    #[case(KeyCode::Up, Action::Up)]
+    #[case(KeyCode::Char('k'), Action::Up)]
+    #[case(KeyCode::Char('j'), Action::Down)]
+    #[case(KeyCode::Char('h'), Action::Left)]
+    #[case(KeyCode::Right, Action::Right)]
+    #[case(KeyCode::Enter, Action::Place)]
+    #[case(KeyCode::Char(' '), Action::Place)]
+    #[case(KeyCode::Esc, Action::Quit)]
+    fn keys_map_to_actions(#[case] key: KeyCode, #[case] action: Action) {
+        assert_eq!(action_for(key), Some(action));
+    }
+
+    #[test]
+    fn other_keys_are_ignored() {
+        assert_eq!(action_for(KeyCode::Char('z')), None);
+    }
+
+    #[test]
+    fn cursor_starts_in_the_centre() {
+        assert_eq!(screen_after(&[]).cursor, 4);
+    }
+
+    #[test]
+    fn cursor_moves_and_stops_at_the_edges() {
+        let screen = screen_after(&[Action::Up, Action::Up, Action::Left, Action::Left]);
+        assert_eq!(screen.cursor, 0);
+
+        let screen = screen_after(&[Action::Down, Action::Down, Action::Right, Action::Right]);
+        assert_eq!(screen.cursor, 8);
+    }
+
+    #[test]
+    fn place_plays_the_square_under_the_cursor() {
+        let screen = screen_after(&[Action::Up, Action::Place]);
+
+        assert_eq!(screen.game.board()[1], Symbol::Cross);
+        assert!(screen
+            .lines()
+            .iter()
+            .any(|line| line.starts_with("O to play")));
+    }
+
+    #[test]
+    fn play_errors_are_shown_in_the_status_bar() {
+        let screen = screen_after(&[Action::Place, Action::Place]);
+
+        assert_eq!(
+            screen.message,
+            Some(String::from("That square is already taken"))
+        );
+        assert!(screen
+            .lines()
+            .contains(&String::from("That square is already taken")));
+    }
+
+    #[test]
+    fn messages_clear_on_the_next_action() {
+        let screen = screen_after(&[Action::Undo, Action::Left]);
+
+        assert_eq!(screen.message, None);
+    }
+
+    // X takes the top row while O plays in the middle row.
+    const CROSS_WINS: [Action; 10] = [
+        Action::Up,
+        Action::Place,
+        Action::Down,
+        Action::Place,
+        Action::Up,
+        Action::Left,
+        Action::Place,
+        Action::Down,
+        Action::Place,
+        Action::Up,
+    ];
+
+    #[test]
+    fn wins_are_added_to_the_score() {
+        let mut actions = CROSS_WINS.to_vec();
+        actions.extend(&[Action::Right, Action::Right, Action::Place]);
+        let screen = screen_after(&actions);
+
+        assert_eq!(
+            screen.score,
+            Score {
+                crosses: 1,
+                ..Score::default()
+            }
+        );
+        assert!(screen.lines()[9].starts_with("X wins! | Score X 1 O 0 Draws 0"));
+    }
+
+    #[rstest]
+    // Undone and played again.
+    #[case(&[Action::Undo, Action::Place])]
+    // Undone and redone.
+    #[case(&[Action::Undo, Action::Redo])]
+    #[case(&[Action::Undo, Action::Undo, Action::Redo, Action::Redo])]
+    fn a_win_is_only_counted_once(#[case] after_winning: &[Action]) {
+        let mut actions = CROSS_WINS.to_vec();
+        actions.extend(&[Action::Right, Action::Right, Action::Place]);
+        actions.extend(after_winning);
+        let screen = screen_after(&actions);
+
+        assert_eq!(screen.game.get_winner(), Some(Player::Cross));
+        assert_eq!(
+            screen.score,
+            Score {
+                crosses: 1,
+                ..Score::default()
+            }
+        );
+    }
+
+    #[test]
+    fn each_new_game_is_counted() {
+        let mut actions = CROSS_WINS.to_vec();
+        actions.extend(&[Action::Right, Action::Right, Action::Place]);
+        actions.push(Action::NewGame);
+        actions.extend(CROSS_WINS.iter());
+        actions.extend(&[Action::Right, Action::Right, Action::Place]);
+        let screen = screen_after(&actions);
+
+        assert_eq!(screen.score.crosses, 2);
+    }
+
+    #[test]
+    fn new_game_clears_the_board_but_keeps_the_score() {
+        let mut actions = CROSS_WINS.to_vec();
+        actions.extend(&[Action::Right, Action::Right, Action::Place]);
+        actions.extend(&[Action::Place, Action::NewGame]);
+        let screen = screen_after(&actions);
+
+        assert_eq!(screen.game, Game::start());
+        assert_eq!(screen.score.crosses, 1);
+        assert_eq!(screen.cursor, 4);
+    }
+
+    #[test]
+    fn quit_stops_the_screen() {
+        let mut screen = Screen::new(Game::start());
+
+        assert!(screen.handle(Action::Left));
+        assert!(!screen.handle(Action::Quit));
+    }
+}
@@ -0,0 +1,279 @@
+//! Ultimate tic-tac-toe: nine small boards laid out in a 3x3 grid. The square
+//! played in a small board picks which board the opponent plays in next, and
+//! winning a small board claims that cell of the big board. Three claimed
+//! cells in a row win the game.
+
+use crate::coord::Coord;
+use crate::game::{Game, GameStatus, PlayError, Player, Rules};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Ultimate {
+    boards: Vec<Game>,
+    /// The big board, with a cell claimed for each small board won.
+    meta: Game,
+    current_player: Player,
+    next_board: Option<usize>,
+}
+
+impl Ultimate {
+    pub fn start() -> Ultimate {
+        let rules = Rules::classic();
+
+        Ultimate {
+            boards: vec![Game::start(); rules.squares()],
+            meta: Game::start(),
+            current_player: Player::Cross,
+            next_board: None,
+        }
+    }
+
+    pub fn boards(&self) -> &[Game] {
+        &self.boards
+    }
+
+    pub fn meta(&self) -> &Game {
+        &self.meta
+    }
+
+    /// The board the next move has to be played in, or `None` if any board
+    /// that's still open will do.
+    pub fn next_board(&self) -> Option<usize> {
+        self.next_board
+    }
+
+    /// Whether the small board at `index` can still be played in.
+    pub fn is_open(&self, index: usize) -> bool {
+        matches!(
+            self.boards.get(index).map(Game::status),
+            Some(GameStatus::InProgress { .. })
+        )
+    }
+
+    /// Plays `square` of the small board at `board` for the current player.
+    pub fn play(&mut self, board: usize, square: usize) -> Result<(), UltimateError> {
+        if !matches!(self.status(), GameStatus::InProgress { .. }) {
+            return Err(UltimateError::GameOver);
+        }
+
+        if board >= self.boards.len() {
+            return Err(UltimateError::OutOfRange);
+        }
+
+        if let Some(required) = self.next_board {
+            if board != required {
+                return Err(UltimateError::WrongBoard { required });
+            }
+        }
+
+        if !self.is_open(board) {
+            return Err(UltimateError::BoardFinished(board));
+        }
+
+        let player = self.current_player;
+        self.boards[board]
+            .play_as(player, square)
+            .map_err(|error| match error {
+                PlayError::SquareTaken { .. } => UltimateError::SquareTaken,
+                PlayError::OutOfRange { .. } => UltimateError::OutOfRange,
+                PlayError::GameOver => UltimateError::BoardFinished(board),
+            })?;
+
+        if self.boards[board].get_winner() == Some(player) {
+            self.meta.play_as(player, board).ok();
+        }
+
+        // Sending a player to a finished board lets them play anywhere.
+        self.next_board = Some(square).filter(|square| self.is_open(*square));
+        self.current_player = player.other();
+
+        Ok(())
+    }
+
+    /// Won once a player claims a line of the big board, with the line's
+    /// cells, and drawn once every small board is finished without one.
+    pub fn status(&self) -> GameStatus {
+        if let GameStatus::Won { by, line } = self.meta.status() {
+            return GameStatus::Won { by, line };
+        }
+
+        if (0..self.boards.len()).all(|index| !self.is_open(index)) {
+            return GameStatus::Draw;
+        }
+
+        GameStatus::InProgress {
+            next: self.current_player,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UltimateError {
+    WrongBoard { required: usize },
+    BoardFinished(usize),
+    SquareTaken,
+    OutOfRange,
+    GameOver,
+}
+
+impl fmt::Display for UltimateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UltimateError::WrongBoard { required } => write!(
+                f,
+                "you have to play on board {} ({})",
+                Coord::from_index(*required, Rules::classic()),
+                required
+            ),
+            UltimateError::BoardFinished(board) => write!(
+                f,
+                "board {} ({}) is already finished",
+                Coord::from_index(*board, Rules::classic()),
+                board
+            ),
+            UltimateError::SquareTaken => write!(f, "that square is already taken"),
+            UltimateError::OutOfRange => write!(f, "that square is off the board"),
+            UltimateError::GameOver => write!(f, "the game is already over"),
+        }
+    }
+}
+
+impl Error for UltimateError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Symbol;
+    use rstest::rstest;
+
+    // X takes the middle row of the top three boards, while O's replies in
+    // boards 3 to 5 send X back to them.
+    const CROSS_WINS: &[(usize, usize)] = &[
+        (0, 3),
+        (3, 0),
+        (0, 4),
+        (4, 0),
+        (0, 5),
+        (5, 1),
+        (1, 3),
+        (3, 1),
+        (1, 4),
+        (4, 1),
+        (1, 5),
+        (5, 2),
+        (2, 3),
+        (3, 2),
+        (2, 4),
+        (4, 2),
+        (2, 5),
+    ];
+
+    fn play_moves(moves: &[(usize, usize)]) -> Ultimate {
+        let mut game = Ultimate::start();
+        for (board, square) in moves {
+            assert_eq!(
+                game.play(*board, *square),
+                Ok(()),
+                "board {} square {}",
+                board,
+                square
+            );
+        }
+        game
+    }
+
+    #[test]
+    fn the_first_move_can_be_anywhere() {
+        let game = Ultimate::start();
+
+        assert_eq!(game.next_board(), None);
+        assert_eq!(
+            game.status(),
+            GameStatus::InProgress {
+                next: Player::Cross
+            }
+        );
+        assert!((0..9).all(|board| game.is_open(board)));
+    }
+
+    #[test]
+    fn the_square_played_picks_the_next_board() {
+        let game = play_moves(&[(4, 2)]);
+
+        assert_eq!(game.boards()[4].board()[2], Symbol::Cross);
+        assert_eq!(game.next_board(), Some(2));
+        assert_eq!(
+            game.status(),
+            GameStatus::InProgress {
+                next: Player::Naught
+            }
+        );
+    }
+
+    #[rstest]
+    #[case(&[(4, 2)], 3, 0, UltimateError::WrongBoard { required: 2 })]
+    #[case(&[(4, 4)], 4, 4, UltimateError::SquareTaken)]
+    #[case(&[], 9, 0, UltimateError::OutOfRange)]
+    #[case(&[], 0, 9, UltimateError::OutOfRange)]
+    fn illegal_moves_are_rejected(
+        #[case] moves: &[(usize, usize)],
+        #[case] board: usize,
+        #[case] square: usize,
+        #[case] expected: UltimateError,
+    ) {
+        let mut game = play_moves(moves);
+        let before = game.clone();
+
+        assert_eq!(game.play(board, square), Err(expected));
+        assert_eq!(game, before);
+    }
+
+    #[test]
+    fn winning_a_small_board_claims_its_cell() {
+        let game = play_moves(&CROSS_WINS[..5]);
+
+        assert_eq!(game.boards()[0].get_winner(), Some(Player::Cross));
+        assert_eq!(game.meta().board()[0], Symbol::Cross);
+        assert!(!game.is_open(0));
+    }
+
+    #[test]
+    fn being_sent_to_a_finished_board_frees_the_choice() {
+        // X has won board 0, then O's move in its top left square sends X
+        // back there.
+        let mut game = play_moves(&CROSS_WINS[..5]);
+        game.play(5, 0).unwrap();
+
+        assert_eq!(game.next_board(), None);
+        assert_eq!(game.play(0, 0), Err(UltimateError::BoardFinished(0)));
+        assert_eq!(game.play(8, 8), Ok(()));
+    }
+
+    #[test]
+    fn a_line_of_small_boards_wins() {
+        let mut game = play_moves(CROSS_WINS);
+
+        assert_eq!(
+            game.status(),
+            GameStatus::Won {
+                by: Player::Cross,
+                line: vec![0, 1, 2]
+            }
+        );
+        assert_eq!(game.meta().board()[3], Symbol::Naught);
+        assert_eq!(game.play(6, 0), Err(UltimateError::GameOver));
+    }
+
+    #[test]
+    fn errors_name_the_board() {
+        assert_eq!(
+            UltimateError::WrongBoard { required: 5 }.to_string(),
+            "you have to play on board C2 (5)"
+        );
+        assert_eq!(
+            UltimateError::BoardFinished(0).to_string(),
+            "board A1 (0) is already finished"
+        );
+    }
+}
@@ -0,0 +1,685 @@
+//! Rule variants. Each variant decides which moves are legal, what a move
+//! does and when the game is over, so the computer can play any of them.
+//! `Game` itself is the standard game.
+
+use crate::book::Book;
+use crate::coord::{self, SquareError};
+use crate::game::{Game, GameStatus, PlayError, Player, Rules};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+pub trait Variant: Clone {
+    type Move: Copy + PartialEq + fmt::Debug + fmt::Display;
+    type Error: Error;
+
+    /// The moves the player to move can make, none once the game is over.
+    fn legal_moves(&self) -> Vec<Self::Move>;
+
+    fn apply(&mut self, mv: Self::Move) -> Result<(), Self::Error>;
+
+    /// `Won` names the player who won, who isn't always the one who made
+    /// the line.
+    fn status(&self) -> GameStatus;
+
+    fn current_player(&self) -> Player;
+
+    /// Reads a move as a player types it, without checking it's legal.
+    fn parse_move(&self, text: &str) -> Result<Self::Move, ParseMoveError>;
+
+    /// The move `book` gives for this position. Books are made for the
+    /// standard game, so other variants never find one.
+    fn book_move(&self, _book: &Book) -> Option<Self::Move> {
+        None
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ParseMoveError {
+    Square(SquareError),
+    /// A wild move needs the symbol to play after the square.
+    MissingSymbol,
+    /// A numerical move needs the number to play after the square.
+    MissingNumber,
+}
+
+impl fmt::Display for ParseMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseMoveError::Square(error) => write!(f, "{}", error),
+            ParseMoveError::MissingSymbol => {
+                write!(f, "choose X or O after the square, e.g. B2 O")
+            }
+            ParseMoveError::MissingNumber => {
+                write!(f, "choose a number after the square, e.g. B2 5")
+            }
+        }
+    }
+}
+
+impl Error for ParseMoveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseMoveError::Square(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<SquareError> for ParseMoveError {
+    fn from(error: SquareError) -> ParseMoveError {
+        ParseMoveError::Square(error)
+    }
+}
+
+/// The variants that can be chosen with `--variant`.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum Kind {
+    #[default]
+    Standard,
+    Misere,
+    Wild,
+    Numerical,
+}
+
+impl Kind {
+    pub fn description(self) -> &'static str {
+        match self {
+            Kind::Standard => "Standard rules, make a line to win",
+            Kind::Misere => "Misère rules, whoever makes a line loses",
+            Kind::Wild => "Wild rules, play X or O and make a line of either to win",
+            Kind::Numerical => {
+                "Numerical rules, X plays odd numbers and O even, make a line adding up to 15 to win"
+            }
+        }
+    }
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Kind, String> {
+        match value.to_lowercase().as_str() {
+            "standard" => Ok(Kind::Standard),
+            "misere" | "misère" => Ok(Kind::Misere),
+            "wild" => Ok(Kind::Wild),
+            "numerical" => Ok(Kind::Numerical),
+            _ => Err(format!(
+                "Unknown variant '{}', expected standard, misere, wild or numerical",
+                value
+            )),
+        }
+    }
+}
+
+impl Variant for Game {
+    type Move = usize;
+    type Error = PlayError;
+
+    fn legal_moves(&self) -> Vec<usize> {
+        match Game::status(self) {
+            GameStatus::InProgress { .. } => self.available_moves(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn apply(&mut self, index: usize) -> Result<(), PlayError> {
+        self.play(index)
+    }
+
+    fn status(&self) -> GameStatus {
+        Game::status(self)
+    }
+
+    fn current_player(&self) -> Player {
+        Game::current_player(self)
+    }
+
+    fn parse_move(&self, text: &str) -> Result<usize, ParseMoveError> {
+        Ok(coord::parse_square(text, self.rules())?)
+    }
+
+    fn book_move(&self, book: &Book) -> Option<usize> {
+        book.best_move(self)
+    }
+}
+
+/// Whoever completes a line loses.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Misere {
+    game: Game,
+}
+
+impl Misere {
+    pub fn new(rules: Rules) -> Misere {
+        Misere {
+            game: Game::starting_with(rules, Player::Cross),
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+}
+
+impl Variant for Misere {
+    type Move = usize;
+    type Error = PlayError;
+
+    fn legal_moves(&self) -> Vec<usize> {
+        self.game.legal_moves()
+    }
+
+    fn apply(&mut self, index: usize) -> Result<(), PlayError> {
+        self.game.play(index)
+    }
+
+    fn status(&self) -> GameStatus {
+        match self.game.status() {
+            GameStatus::Won { by, line } => GameStatus::Won {
+                by: by.other(),
+                line,
+            },
+            status => status,
+        }
+    }
+
+    fn current_player(&self) -> Player {
+        self.game.current_player()
+    }
+
+    fn parse_move(&self, text: &str) -> Result<usize, ParseMoveError> {
+        self.game.parse_move(text)
+    }
+}
+
+/// Either player can place either symbol, and whoever completes a line of
+/// one symbol wins.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Wild {
+    game: Game,
+    current_player: Player,
+    winner: Option<Player>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct WildMove {
+    pub square: usize,
+    pub symbol: Player,
+}
+
+impl fmt::Display for WildMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} on {}", self.symbol, self.square)
+    }
+}
+
+impl Wild {
+    pub fn new(rules: Rules) -> Wild {
+        Wild {
+            game: Game::starting_with(rules, Player::Cross),
+            current_player: Player::Cross,
+            winner: None,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+}
+
+impl Variant for Wild {
+    type Move = WildMove;
+    type Error = PlayError;
+
+    fn legal_moves(&self) -> Vec<WildMove> {
+        self.game
+            .legal_moves()
+            .into_iter()
+            .flat_map(|square| {
+                [Player::Cross, Player::Naught]
+                    .iter()
+                    .map(move |symbol| WildMove {
+                        square,
+                        symbol: *symbol,
+                    })
+                    .collect::<Vec<WildMove>>()
+            })
+            .collect()
+    }
+
+    fn apply(&mut self, mv: WildMove) -> Result<(), PlayError> {
+        self.game.play_as(mv.symbol, mv.square)?;
+        if self.game.get_winner().is_some() {
+            self.winner = Some(self.current_player);
+        }
+        self.current_player = self.current_player.other();
+
+        Ok(())
+    }
+
+    fn status(&self) -> GameStatus {
+        match (self.game.status(), self.winner) {
+            (GameStatus::Won { line, .. }, Some(by)) => GameStatus::Won { by, line },
+            (GameStatus::InProgress { .. }, _) => GameStatus::InProgress {
+                next: self.current_player,
+            },
+            (status, _) => status,
+        }
+    }
+
+    fn current_player(&self) -> Player {
+        self.current_player
+    }
+
+    /// A square and the symbol to put there, e.g. `B2 O`.
+    fn parse_move(&self, text: &str) -> Result<WildMove, ParseMoveError> {
+        let mut parts = text.split_whitespace();
+        let square = coord::parse_square(parts.next().unwrap_or_default(), self.game.rules())?;
+        let symbol = match parts.next().map(str::to_uppercase).as_deref() {
+            Some("X") => Player::Cross,
+            Some("O") => Player::Naught,
+            _ => return Err(ParseMoveError::MissingSymbol),
+        };
+
+        Ok(WildMove { square, symbol })
+    }
+}
+
+/// The eight lines of a 3x3 board.
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+/// The total a line needs to win numerical tic-tac-toe.
+pub const TARGET: u8 = 15;
+
+/// Played on a 3x3 board with the numbers 1 to 9, each used once. X plays
+/// the odd numbers and O the even ones, and whoever completes a line of
+/// three numbers adding up to 15 wins.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Numerical {
+    squares: Vec<Option<u8>>,
+    current_player: Player,
+    winner: Option<(Player, Vec<usize>)>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct NumberMove {
+    pub square: usize,
+    pub number: u8,
+}
+
+impl fmt::Display for NumberMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} on {}", self.number, self.square)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NumberError {
+    SquareTaken,
+    OutOfRange,
+    GameOver,
+    /// The number is out of 1 to 9 or belongs to the other player.
+    NotYourNumber(u8),
+    AlreadyUsed(u8),
+}
+
+impl fmt::Display for NumberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NumberError::SquareTaken => write!(f, "that square is already taken"),
+            NumberError::OutOfRange => write!(f, "that square is off the board"),
+            NumberError::GameOver => write!(f, "the game is already over"),
+            NumberError::NotYourNumber(number) => {
+                write!(f, "{} isn't one of your numbers", number)
+            }
+            NumberError::AlreadyUsed(number) => write!(f, "{} has already been used", number),
+        }
+    }
+}
+
+impl Error for NumberError {}
+
+impl Default for Numerical {
+    fn default() -> Numerical {
+        Numerical {
+            squares: vec![None; 9],
+            current_player: Player::Cross,
+            winner: None,
+        }
+    }
+}
+
+impl Numerical {
+    pub fn new() -> Numerical {
+        Numerical::default()
+    }
+
+    /// The number in each square, row by row.
+    pub fn squares(&self) -> &[Option<u8>] {
+        &self.squares
+    }
+
+    /// The numbers `player` hasn't used yet.
+    pub fn numbers_left(&self, player: Player) -> Vec<u8> {
+        let parity = if player == Player::Naught { 0 } else { 1 };
+
+        (1..=9)
+            .filter(|number| number % 2 == parity && !self.squares.contains(&Some(*number)))
+            .collect()
+    }
+}
+
+impl Variant for Numerical {
+    type Move = NumberMove;
+    type Error = NumberError;
+
+    fn legal_moves(&self) -> Vec<NumberMove> {
+        if !matches!(self.status(), GameStatus::InProgress { .. }) {
+            return Vec::new();
+        }
+
+        let numbers = self.numbers_left(self.current_player);
+        (0..self.squares.len())
+            .filter(|square| self.squares[*square].is_none())
+            .flat_map(|square| {
+                numbers
+                    .iter()
+                    .map(move |number| NumberMove {
+                        square,
+                        number: *number,
+                    })
+                    .collect::<Vec<NumberMove>>()
+            })
+            .collect()
+    }
+
+    fn apply(&mut self, mv: NumberMove) -> Result<(), NumberError> {
+        if !matches!(self.status(), GameStatus::InProgress { .. }) {
+            return Err(NumberError::GameOver);
+        }
+
+        match self.squares.get(mv.square) {
+            None => return Err(NumberError::OutOfRange),
+            Some(Some(_)) => return Err(NumberError::SquareTaken),
+            Some(None) => {}
+        }
+
+        if self.squares.contains(&Some(mv.number)) {
+            return Err(NumberError::AlreadyUsed(mv.number));
+        }
+        if !self.numbers_left(self.current_player).contains(&mv.number) {
+            return Err(NumberError::NotYourNumber(mv.number));
+        }
+
+        self.squares[mv.square] = Some(mv.number);
+        let completed = LINES.iter().find(|line| {
+            line.contains(&mv.square)
+                && line
+                    .iter()
+                    .map(|square| self.squares[*square])
+                    .sum::<Option<u8>>()
+                    == Some(TARGET)
+        });
+        if let Some(line) = completed {
+            self.winner = Some((self.current_player, line.to_vec()));
+        }
+        self.current_player = self.current_player.other();
+
+        Ok(())
+    }
+
+    fn status(&self) -> GameStatus {
+        if let Some((by, line)) = &self.winner {
+            return GameStatus::Won {
+                by: *by,
+                line: line.clone(),
+            };
+        }
+
+        if self.squares.iter().all(Option::is_some) {
+            return GameStatus::Draw;
+        }
+
+        GameStatus::InProgress {
+            next: self.current_player,
+        }
+    }
+
+    fn current_player(&self) -> Player {
+        self.current_player
+    }
+
+    /// A square and the number to put there, e.g. `B2 5`.
+    fn parse_move(&self, text: &str) -> Result<NumberMove, ParseMoveError> {
+        let mut parts = text.split_whitespace();
+        let square = coord::parse_square(parts.next().unwrap_or_default(), Rules::classic())?;
+        let number = parts
+            .next()
+            .and_then(|number| number.parse().ok())
+            .ok_or(ParseMoveError::MissingNumber)?;
+
+        Ok(NumberMove { square, number })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Symbol;
+    use rstest::rstest;
+
+    fn play<V: Variant>(mut game: V, moves: &[V::Move]) -> V {
+        for mv in moves {
+            assert!(game.apply(*mv).is_ok(), "{} should be playable", mv);
+        }
+        game
+    }
+
+    fn wild(square: usize, symbol: Player) -> WildMove {
+        WildMove { square, symbol }
+    }
+
+    fn number(square: usize, number: u8) -> NumberMove {
+        NumberMove { square, number }
+    }
+
+    #[test]
+    fn standard_rules_are_the_game() {
+        let game = play(Game::start(), &[0, 3, 1, 4, 2]);
+
+        assert_eq!(Variant::status(&game), game.status());
+        assert!(game.legal_moves().is_empty());
+        assert_eq!(Game::start().legal_moves().len(), 9);
+    }
+
+    #[test]
+    fn completing_a_line_loses_in_misere() {
+        let game = play(Misere::new(Rules::classic()), &[0, 3, 1, 4, 2]);
+
+        assert_eq!(
+            game.status(),
+            GameStatus::Won {
+                by: Player::Naught,
+                line: vec![0, 1, 2]
+            }
+        );
+        assert!(game.legal_moves().is_empty());
+    }
+
+    #[test]
+    fn either_symbol_can_be_played_in_wild() {
+        let game = play(Wild::new(Rules::classic()), &[wild(0, Player::Naught)]);
+
+        assert_eq!(game.game().board()[0], Symbol::Naught);
+        assert_eq!(game.current_player(), Player::Naught);
+        assert_eq!(game.legal_moves().len(), 16);
+    }
+
+    #[test]
+    fn completing_a_line_of_either_symbol_wins_in_wild() {
+        // O makes the line of Os on X's turn.
+        let game = play(
+            Wild::new(Rules::classic()),
+            &[
+                wild(0, Player::Naught),
+                wild(4, Player::Cross),
+                wild(1, Player::Naught),
+                wild(8, Player::Cross),
+                wild(2, Player::Naught),
+            ],
+        );
+
+        assert_eq!(
+            game.status(),
+            GameStatus::Won {
+                by: Player::Cross,
+                line: vec![0, 1, 2]
+            }
+        );
+    }
+
+    #[test]
+    fn numbers_are_split_by_parity() {
+        let game = play(Numerical::new(), &[number(4, 5)]);
+
+        assert_eq!(game.numbers_left(Player::Cross), [1, 3, 7, 9]);
+        assert_eq!(game.numbers_left(Player::Naught), [2, 4, 6, 8]);
+        assert_eq!(game.legal_moves().len(), 8 * 4);
+    }
+
+    #[rstest]
+    #[case(&[], number(0, 2), NumberError::NotYourNumber(2))]
+    #[case(&[], number(0, 10), NumberError::NotYourNumber(10))]
+    #[case(&[number(0, 1), number(1, 2)], number(2, 1), NumberError::AlreadyUsed(1))]
+    #[case(&[number(0, 1)], number(0, 2), NumberError::SquareTaken)]
+    #[case(&[], number(9, 1), NumberError::OutOfRange)]
+    fn bad_numbers_are_rejected(
+        #[case] moves: &[NumberMove],
+        #[case] mv: NumberMove,
+        #[case] expected: NumberError,
+    ) {
+        let mut game = play(Numerical::new(), moves);
+
+        assert_eq!(game.apply(mv), Err(expected));
+    }
+
+    #[rstest]
+    #[case(NumberError::SquareTaken, "that square is already taken")]
+    #[case(NumberError::NotYourNumber(2), "2 isn't one of your numbers")]
+    #[case(NumberError::AlreadyUsed(1), "1 has already been used")]
+    fn number_errors_can_be_shown(#[case] error: NumberError, #[case] expected: &str) {
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn a_line_adding_up_to_15_wins() {
+        // O's 8 completes the top row as 1 + 6 + 8, with numbers from both
+        // players.
+        let mut game = play(
+            Numerical::new(),
+            &[number(0, 1), number(1, 6), number(4, 3)],
+        );
+        assert_eq!(
+            game.status(),
+            GameStatus::InProgress {
+                next: Player::Naught
+            }
+        );
+
+        game.apply(number(2, 8)).unwrap();
+
+        assert_eq!(
+            game.status(),
+            GameStatus::Won {
+                by: Player::Naught,
+                line: vec![0, 1, 2]
+            }
+        );
+        assert_eq!(game.apply(number(8, 5)), Err(NumberError::GameOver));
+    }
+
+    #[test]
+    fn a_full_board_without_15_is_a_draw() {
+        // Every line adds up to something other than 15.
+        let game = play(
+            Numerical::new(),
+            &[
+                number(0, 1),
+                number(1, 2),
+                number(2, 3),
+                number(3, 4),
+                number(4, 5),
+                number(6, 6),
+                number(5, 7),
+                number(8, 8),
+                number(7, 9),
+            ],
+        );
+
+        assert_eq!(game.status(), GameStatus::Draw);
+    }
+
+    #[rstest]
+    #[case("standard", Kind::Standard)]
+    #[case("Misere", Kind::Misere)]
+    #[case("misère", Kind::Misere)]
+    #[case("wild", Kind::Wild)]
+    #[case("numerical", Kind::Numerical)]
+    fn variants_are_parsed_by_name(#[case] name: &str, #[case] expected: Kind) {
+        assert_eq!(name.parse(), Ok(expected));
+    }
+
+    #[test]
+    fn unknown_variants_are_rejected() {
+        assert!("chess".parse::<Kind>().is_err());
+    }
+
+    #[rstest]
+    #[case("B2 o", Ok(WildMove { square: 4, symbol: Player::Naught }))]
+    #[case("4 X", Ok(WildMove { square: 4, symbol: Player::Cross }))]
+    #[case("B2", Err(ParseMoveError::MissingSymbol))]
+    #[case("B2 Z", Err(ParseMoveError::MissingSymbol))]
+    #[case(
+        "D1 X",
+        Err(ParseMoveError::Square(SquareError::OffBoard(String::from("D1"))))
+    )]
+    fn wild_moves_are_read_with_their_symbol(
+        #[case] text: &str,
+        #[case] expected: Result<WildMove, ParseMoveError>,
+    ) {
+        assert_eq!(Wild::new(Rules::classic()).parse_move(text), expected);
+    }
+
+    #[rstest]
+    #[case("C3 5", Ok(NumberMove { square: 8, number: 5 }))]
+    #[case("C3", Err(ParseMoveError::MissingNumber))]
+    #[case("C3 five", Err(ParseMoveError::MissingNumber))]
+    fn numerical_moves_are_read_with_their_number(
+        #[case] text: &str,
+        #[case] expected: Result<NumberMove, ParseMoveError>,
+    ) {
+        assert_eq!(Numerical::new().parse_move(text), expected);
+    }
+
+    #[test]
+    fn standard_and_misere_moves_are_squares() {
+        let rules = Rules::new(4, 4, 3).unwrap();
+
+        assert_eq!(
+            Game::starting_with(rules, Player::Cross).parse_move("D1"),
+            Ok(3)
+        );
+        assert_eq!(Misere::new(rules).parse_move("15"), Ok(15));
+        assert!(Misere::new(rules).parse_move("16").is_err());
+    }
+}
@@ -0,0 +1,256 @@
+//! Every position reachable in classic tic-tac-toe, checked against rules
+//! and results worked out here independently of the engine.
+
+use std::collections::HashMap;
+use tic_tac_toe::analysis::{Evaluation, Solver, Verdict};
+use tic_tac_toe::{Game, GameStatus, PlayError, Player, Symbol};
+
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+/// Every reachable position, keyed by its board, found by playing every
+/// legal move from the start.
+fn reachable_positions() -> HashMap<Vec<Symbol>, Game> {
+    let mut positions = HashMap::new();
+    let mut to_visit = vec![Game::start()];
+
+    while let Some(game) = to_visit.pop() {
+        if positions.contains_key(game.board()) {
+            continue;
+        }
+        if let GameStatus::InProgress { .. } = game.status() {
+            for index in game.available_moves() {
+                let mut next = game.clone();
+                next.play(index).unwrap();
+                to_visit.push(next);
+            }
+        }
+        positions.insert(game.board().to_vec(), game);
+    }
+
+    positions
+}
+
+fn has_line(board: &[Symbol], player: Player) -> bool {
+    LINES
+        .iter()
+        .any(|line| line.iter().all(|index| board[*index] == player.into()))
+}
+
+fn count(board: &[Symbol], symbol: Symbol) -> usize {
+    board.iter().filter(|square| **square == symbol).count()
+}
+
+/// A plain minimax over boards, scoring quicker wins and slower losses
+/// higher, to check the solver against.
+fn perfect_play(
+    board: &mut Vec<Symbol>,
+    to_move: Player,
+    known: &mut HashMap<Vec<Symbol>, Evaluation>,
+) -> Evaluation {
+    if let Some(evaluation) = known.get(board.as_slice()) {
+        return *evaluation;
+    }
+
+    let evaluation = if has_line(board, to_move.other()) {
+        Evaluation {
+            verdict: Verdict::Loss,
+            distance: 0,
+        }
+    } else if !board.contains(&Symbol::Empty) {
+        Evaluation {
+            verdict: Verdict::Draw,
+            distance: 0,
+        }
+    } else {
+        let mut best: Option<(i64, Evaluation)> = None;
+        for index in 0..board.len() {
+            if board[index] != Symbol::Empty {
+                continue;
+            }
+            board[index] = to_move.into();
+            let reply = perfect_play(board, to_move.other(), known);
+            board[index] = Symbol::Empty;
+
+            let evaluation = Evaluation {
+                verdict: match reply.verdict {
+                    Verdict::Win => Verdict::Loss,
+                    Verdict::Draw => Verdict::Draw,
+                    Verdict::Loss => Verdict::Win,
+                },
+                distance: reply.distance + 1,
+            };
+            let distance = i64::from(evaluation.distance);
+            let score = match evaluation.verdict {
+                Verdict::Win => 100 - distance,
+                Verdict::Draw => 0,
+                Verdict::Loss => distance - 100,
+            };
+            let better = match best {
+                Some((best_score, _)) => score > best_score,
+                None => true,
+            };
+            if better {
+                best = Some((score, evaluation));
+            }
+        }
+        best.unwrap().1
+    };
+
+    known.insert(board.clone(), evaluation);
+    evaluation
+}
+
+#[test]
+fn there_are_5478_reachable_positions() {
+    let positions = reachable_positions();
+
+    let mut wins = HashMap::new();
+    let mut draws = 0;
+    for game in positions.values() {
+        match game.status() {
+            GameStatus::Won { by, .. } => *wins.entry(by).or_insert(0) += 1,
+            GameStatus::Draw => draws += 1,
+            GameStatus::InProgress { .. } => {}
+        }
+    }
+
+    assert_eq!(positions.len(), 5478);
+    assert_eq!(wins.get(&Player::Cross), Some(&626));
+    assert_eq!(wins.get(&Player::Naught), Some(&316));
+    assert_eq!(draws, 16);
+}
+
+#[test]
+fn piece_counts_follow_the_turns() {
+    for (board, game) in reachable_positions() {
+        let crosses = count(&board, Symbol::Cross);
+        let naughts = count(&board, Symbol::Naught);
+
+        assert!(crosses == naughts || crosses == naughts + 1, "{}", game);
+        assert_eq!(crosses + naughts, game.history().len(), "{}", game);
+        let next = if crosses == naughts {
+            Player::Cross
+        } else {
+            Player::Naught
+        };
+        assert_eq!(game.current_player(), next, "{}", game);
+    }
+}
+
+#[test]
+fn there_is_at_most_one_winner() {
+    for (board, game) in reachable_positions() {
+        let crosses_won = has_line(&board, Player::Cross);
+        let naughts_won = has_line(&board, Player::Naught);
+
+        assert!(!(crosses_won && naughts_won), "{}", game);
+        let expected = match (crosses_won, naughts_won) {
+            (true, _) => Some(Player::Cross),
+            (_, true) => Some(Player::Naught),
+            _ => None,
+        };
+        assert_eq!(game.get_winner(), expected, "{}", game);
+    }
+}
+
+#[test]
+fn the_status_matches_the_board() {
+    for (board, game) in reachable_positions() {
+        match game.status() {
+            GameStatus::Won { by, line } => {
+                assert_eq!(game.get_winner(), Some(by), "{}", game);
+                assert!(LINES.iter().any(|known| known[..] == line[..]), "{}", game);
+                assert!(line.iter().all(|index| board[*index] == by.into()));
+            }
+            GameStatus::Draw => {
+                assert_eq!(game.get_winner(), None, "{}", game);
+                assert!(!board.contains(&Symbol::Empty), "{}", game);
+            }
+            GameStatus::InProgress { next } => {
+                assert_eq!(game.get_winner(), None, "{}", game);
+                assert!(board.contains(&Symbol::Empty), "{}", game);
+                assert_eq!(next, game.current_player());
+            }
+        }
+    }
+}
+
+#[test]
+fn taken_squares_cannot_be_played() {
+    for (board, game) in reachable_positions() {
+        for (index, square) in board.iter().enumerate() {
+            let mut after = game.clone();
+            let result = after.play(index);
+
+            match (square.player(), game.status()) {
+                (_, GameStatus::Won { .. }) | (_, GameStatus::Draw) => {
+                    assert_eq!(result, Err(PlayError::GameOver), "{}", game)
+                }
+                (Some(by), _) => {
+                    assert_eq!(
+                        result,
+                        Err(PlayError::SquareTaken { index, by }),
+                        "{}",
+                        game
+                    )
+                }
+                (None, _) => continue,
+            }
+            assert_eq!(after, game);
+        }
+    }
+}
+
+#[test]
+fn the_solver_agrees_with_plain_minimax() {
+    let mut solver = Solver::new();
+    let mut known = HashMap::new();
+
+    for (mut board, game) in reachable_positions() {
+        let expected = perfect_play(&mut board, game.current_player(), &mut known);
+
+        assert_eq!(solver.evaluate(&game), expected, "{}", game);
+    }
+}
+
+#[test]
+fn known_openings_have_their_usual_results() {
+    let mut solver = Solver::new();
+    let after = |moves: &[usize]| {
+        let mut game = Game::start();
+        for index in moves {
+            game.play(*index).unwrap();
+        }
+        game
+    };
+
+    assert_eq!(
+        solver.evaluate(&Game::start()),
+        Evaluation {
+            verdict: Verdict::Draw,
+            distance: 9
+        }
+    );
+    // Against the centre only a corner holds the draw.
+    assert_eq!(solver.evaluate(&after(&[4, 0])).verdict, Verdict::Draw);
+    assert_eq!(solver.evaluate(&after(&[4, 1])).verdict, Verdict::Win);
+    // Against a corner only the centre holds the draw.
+    assert_eq!(solver.evaluate(&after(&[0, 4])).verdict, Verdict::Draw);
+    for reply in [1, 2, 3, 5, 6, 7, 8] {
+        assert_eq!(
+            solver.evaluate(&after(&[0, reply])).verdict,
+            Verdict::Win,
+            "corner then {}",
+            reply
+        );
+    }
+}
@@ -1,18 +1,794 @@
-use assert_cmd::prelude::*; // Add methods on commands
+use assert_cmd::Command; // Run programs and make assertions on them
 use predicates::prelude::*; // Used for writing assertions
-use std::process::{Command, Stdio}; // Run programs
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
+use std::process::{self, Child, ChildStdout, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The binary with its leaderboard kept in a temporary file of its own, so
+/// tests don't touch the real one or each other's.
+fn tic_tac_toe() -> TicTacToe {
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+    let run = RUNS.fetch_add(1, Ordering::SeqCst);
+    let leaderboard = temp_file(&format!("leaderboard_{}.txt", run));
+
+    let mut cmd = Command::cargo_bin("tic_tac_toe").unwrap();
+    cmd.arg("--leaderboard").arg(&leaderboard);
+    TicTacToe { cmd, leaderboard }
+}
+
+/// A command that removes its leaderboard once it's dropped.
+struct TicTacToe {
+    cmd: Command,
+    leaderboard: PathBuf,
+}
+
+impl Deref for TicTacToe {
+    type Target = Command;
+
+    fn deref(&self) -> &Command {
+        &self.cmd
+    }
+}
+
+impl DerefMut for TicTacToe {
+    fn deref_mut(&mut self) -> &mut Command {
+        &mut self.cmd
+    }
+}
+
+impl Drop for TicTacToe {
+    fn drop(&mut self) {
+        fs::remove_file(&self.leaderboard).ok();
+    }
+}
 
 #[test]
 fn verify_main_menu() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("tic_tac_toe")?;
-
-    let assert = cmd.assert();
+    let mut cmd = tic_tac_toe();
 
-    assert
+    cmd.assert()
         .success()
         .stdout(predicate::str::contains("Tic Tac Toe"))
-        .stdout(predicate::str::contains("1) Play vs Human"));
+        .stdout(predicate::str::contains("1) Play vs Human"))
+        .stdout(predicate::str::contains("2) Play vs Computer"));
+
+    // X takes the top row while O plays the left and middle squares, with a
+    // couple of bad inputs along the way.
+    let mut cmd = tic_tac_toe();
+
+    cmd.write_stdin("1\n\n\n\n0\n0\n9\nfoo\n3\n1\n4\n2\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("X to play"))
+        .stdout(predicate::str::contains("O to play"))
+        .stdout(predicate::str::contains(
+            "Can't play there, square 0 is already taken by X",
+        ))
+        .stdout(predicate::str::contains(
+            "Can't play there, square 9 is off the board, which has squares 0 to 8",
+        ))
+        .stdout(predicate::str::contains("'foo' is not a square"))
+        .stdout(predicate::str::contains("1 [X]|[X]|[X]"))
+        .stdout(predicate::str::contains("X wins!"));
+
+    Ok(())
+}
+
+#[test]
+fn squares_can_be_chosen_by_coordinate() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    // X goes down the diagonal using a mix of notations.
+    cmd.write_stdin("1\n\n\n\nA1\nb1\n2,2\nD1\nC1\nc3\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("choose a square (0-8 or A1-C3)"))
+        .stdout(predicate::str::contains(
+            "Can't play there, square D1 is off the board, which goes from A1 to C3",
+        ))
+        .stdout(predicate::str::contains("X wins!"))
+        .stdout(predicate::str::contains("Moves played: 0, 1, 4, 2, 8"));
+
+    Ok(())
+}
+
+#[test]
+fn board_can_be_shown_in_colour() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    cmd.arg("--colour")
+        .write_stdin("1\n\n\n\nB2\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[1;31mX\x1b[0m"));
+
+    Ok(())
+}
+
+#[test]
+fn full_board_without_a_line_is_a_draw() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    cmd.write_stdin("1\n\n\n\n0\n1\n2\n4\n3\n5\n7\n6\n8\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("It's a draw!"))
+        .stdout(predicate::str::contains("wins!").not());
+
+    Ok(())
+}
+
+#[test]
+fn computer_punishes_a_careless_opponent() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    // The computer takes the centre after X opens in the corner, then wins
+    // down the middle column once X wanders off to the other corners.
+    cmd.write_stdin("2\n\n\nhard\ny\n0\n8\n6\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Choose a difficulty"))
+        .stdout(predicate::str::contains("Do you want to go first?"))
+        .stdout(predicate::str::contains("Computer plays 4"))
+        .stdout(predicate::str::contains("O wins!"));
+
+    Ok(())
+}
+
+#[test]
+fn unknown_difficulties_are_asked_for_again() -> Result<(), Box<dyn std::error::Error>> {
+    tic_tac_toe()
+        .write_stdin("2\n\n\nx\neasy\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "[hard]: Unknown difficulty 'x', expected easy, medium or hard\n\
+             Choose a difficulty (easy/medium/hard) [hard]: ",
+        ))
+        .stdout(predicate::str::contains("Can't read that move").not());
+
+    Ok(())
+}
+
+#[test]
+fn computer_can_go_first() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    cmd.args(["--difficulty", "hard"])
+        .write_stdin("2\n\n\nn\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Computer plays 0"))
+        .stdout(predicate::str::contains("O to play"));
+
+    Ok(())
+}
+
+#[test]
+fn seeded_easy_computer_plays_the_same_game_every_time() -> Result<(), Box<dyn std::error::Error>> {
+    // Each run starts from an empty leaderboard so the ratings match too.
+    let play = |name: &str| -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let leaderboard = temp_file(name);
+        let output = tic_tac_toe()
+            .args(["--difficulty", "easy", "--seed", "3", "--leaderboard"])
+            .arg(&leaderboard)
+            .write_stdin("2\n\n\nn\n0\n1\n2\n3\n4\n5\n6\n7\n8\n")
+            .output()?;
+        fs::remove_file(&leaderboard)?;
+        Ok(output.stdout)
+    };
+
+    assert_eq!(play("seeded_first.txt")?, play("seeded_second.txt")?);
+
+    Ok(())
+}
+
+#[test]
+fn unknown_arguments_are_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    cmd.args(["--difficulty", "impossible"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown difficulty 'impossible'"));
+
+    Ok(())
+}
+
+#[test]
+fn unreadable_input_is_an_error_not_a_panic() -> Result<(), Box<dyn std::error::Error>> {
+    // Bytes that aren't UTF-8, at the menu and part way through a game.
+    for input in [&b"\xff\n"[..], &b"1\n\n\n\n4\n\xff\n"[..]] {
+        tic_tac_toe()
+            .write_stdin(input)
+            .assert()
+            .failure()
+            .code(1)
+            .stderr(predicate::str::contains("Couldn't use the terminal"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn board_size_and_win_length_can_be_chosen() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    // X gets four in a row along the top of a 4x4 board.
+    cmd.args(["--size", "4x4", "--win", "4"])
+        .write_stdin("1\n\n\n\n0\n4\n1\n5\n2\n6\n3\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("4x4 board, 4 in a row to win"))
+        .stdout(predicate::str::contains("choose a square (0-15 or A1-D4)"))
+        .stdout(predicate::str::contains("X wins!"));
+
+    Ok(())
+}
+
+#[test]
+fn impossible_rules_are_rejected() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    cmd.args(["--size", "3x3", "--win", "4"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("doesn't fit on the board"));
+
+    tic_tac_toe()
+        .args(["--size", "100000x100000"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("at most"));
+
+    Ok(())
+}
+
+#[test]
+fn moves_can_be_undone_and_redone() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    // X plays 4, takes it back and plays 0 instead. O's 3 is undone and
+    // redone before X completes the top row.
+    cmd.write_stdin("1\n\n\n\nu\n4\nu\n0\n3\nu\nr\nr\n1\n4\n2\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Commands: u to undo, r to redo"))
+        .stdout(predicate::str::contains("Nothing to undo"))
+        .stdout(predicate::str::contains("Nothing to redo"))
+        .stdout(predicate::str::contains("1 [X]|[X]|[X]"))
+        .stdout(predicate::str::contains("X wins!"))
+        .stdout(predicate::str::contains("Moves played: 0, 3, 1, 4, 2"));
+
+    Ok(())
+}
+
+#[test]
+fn undo_against_the_computer_takes_back_its_reply() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    // The computer answers X's corner with the centre both times, which
+    // only happens twice if undo took back both moves.
+    cmd.args(["--difficulty", "hard"])
+        .write_stdin("2\n\n\ny\n0\nu\n0\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Computer plays 4").count(2));
+
+    Ok(())
+}
+
+#[test]
+fn a_series_alternates_who_goes_first() -> Result<(), Box<dyn std::error::Error>> {
+    let top_row = "0\n3\n1\n4\n2\n";
+
+    // Whoever goes first takes the top row, so X wins games one and three.
+    tic_tac_toe()
+        .write_stdin(format!(
+            "1\n0\nthree\n3\nAlice\nBob\n{}{}{}",
+            top_row, top_row, top_row
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("'0' is not a number of games"))
+        .stdout(predicate::str::contains("'three' is not a number of games"))
+        .stdout(predicate::str::contains("Game 1 of 3, X goes first"))
+        .stdout(predicate::str::contains("Game 2 of 3, O goes first"))
+        .stdout(predicate::str::contains(
+            "Score after game 2 of 3: X 1, O 1, draws 0",
+        ))
+        .stdout(predicate::str::contains(
+            "Score after game 3 of 3: X 2, O 1, draws 0",
+        ))
+        .stdout(predicate::str::contains("Alice wins the match!"));
+
+    Ok(())
+}
+
+#[test]
+fn best_of_skips_the_series_prompt() -> Result<(), Box<dyn std::error::Error>> {
+    tic_tac_toe()
+        .args(["--best-of", "3"])
+        .write_stdin("1\nAlice\nBob\n0\n3\n1\n4\n2\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("How many games?").not())
+        .stdout(predicate::str::contains("Game 1 of 3, X goes first"));
+
+    Ok(())
+}
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("tic_tac_toe_{}_{}", std::process::id(), name))
+}
+
+#[test]
+fn results_are_kept_on_the_leaderboard() -> Result<(), Box<dyn std::error::Error>> {
+    let path = temp_file("named_leaderboard.txt");
+    fs::write(&path, "this line is damaged\nBob\t0\t0\t1\t1000.0\n")?;
+
+    // Alice takes the top row as X.
+    Command::cargo_bin("tic_tac_toe")?
+        .arg("--leaderboard")
+        .arg(&path)
+        .write_stdin("1\n\nAlice\nBob\n0\n3\n1\n4\n2\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Skipped 1 unreadable lines"))
+        .stdout(predicate::str::contains("X wins!"))
+        .stdout(predicate::str::contains("Ratings: Alice 1016, Bob 984"));
+
+    Command::cargo_bin("tic_tac_toe")?
+        .arg("--leaderboard")
+        .arg(&path)
+        .write_stdin("3\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3) Leaderboard"))
+        .stdout(predicate::str::contains(
+            "1    Alice    1016    1    0    0     100%",
+        ))
+        .stdout(predicate::str::contains(
+            "2    Bob       984    0    1    1       0%",
+        ));
+
+    fs::remove_file(&path)?;
+
+    Ok(())
+}
+
+#[test]
+fn names_that_cannot_be_stored_are_asked_for_again() -> Result<(), Box<dyn std::error::Error>> {
+    tic_tac_toe()
+        .write_stdin("1\n\nAl\tice\nAlice\n\n0\n3\n1\n4\n2\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Names can't be blank or contain tabs or line breaks",
+        ))
+        .stdout(predicate::str::contains(
+            "Ratings: Alice 1016, Player O 984",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn an_empty_leaderboard_says_so() -> Result<(), Box<dyn std::error::Error>> {
+    tic_tac_toe()
+        .arg("--leaderboard")
+        .arg(temp_file("empty_leaderboard.txt"))
+        .write_stdin("3\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No games have been recorded yet"));
+
+    Ok(())
+}
+
+#[test]
+fn games_can_be_saved_and_loaded() -> Result<(), Box<dyn std::error::Error>> {
+    let path = temp_file("saved_game.txt");
+    let path_text = path.to_str().unwrap();
+
+    tic_tac_toe()
+        .write_stdin(format!("1\n\n\n\n0\n3\n1\nsave {}\n", path_text))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Saved game to"));
+
+    assert_eq!(fs::read_to_string(&path)?, "3x3:3 XX./O../... O 0,3,1 -\n");
+
+    // A fresh game picks up where the saved one left off, so O's 4 and X's
+    // 2 complete the top row.
+    tic_tac_toe()
+        .write_stdin(format!("1\n\n\n\nload {}\n4\n2\n", path_text))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Loaded game from"))
+        .stdout(predicate::str::contains("X wins!"))
+        .stdout(predicate::str::contains("Moves played: 0, 3, 1, 4, 2"));
+
+    fs::remove_file(&path)?;
+
+    Ok(())
+}
+
+#[test]
+fn loading_a_bad_file_reports_an_error() -> Result<(), Box<dyn std::error::Error>> {
+    let path = temp_file("bad_game.txt");
+    fs::write(&path, "3x3:3 .../.../... Y - -\n")?;
+
+    tic_tac_toe()
+        .write_stdin(format!(
+            "1\n\n\n\nload {}\nload missing.txt\n",
+            path.to_str().unwrap()
+        ))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "doesn't contain a valid game: 'Y' is not a player",
+        ))
+        .stdout(predicate::str::contains("Couldn't read missing.txt"));
+
+    fs::remove_file(&path)?;
+
+    Ok(())
+}
+
+#[test]
+fn positions_can_be_analyzed() -> Result<(), Box<dyn std::error::Error>> {
+    tic_tac_toe()
+        .args(["analyze", "X../.O./..X"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "O to play, draw with perfect play",
+        ))
+        .stdout(predicate::str::contains("  C1     2  X wins in 4 moves"));
+
+    tic_tac_toe()
+        .args(["analyze", "3x3:3 X../.O./... X 0,4 -"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "X to play, draw with perfect play",
+        ));
+
+    tic_tac_toe()
+        .args(["analyze", "XX"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'XX' is not a valid position"));
+
+    tic_tac_toe()
+        .args(["analyze", "4x4:3 ..../..../..../.... X - -"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("at most 12 can be analyzed"));
+
+    Ok(())
+}
+
+#[test]
+fn recorded_games_can_be_replayed() -> Result<(), Box<dyn std::error::Error>> {
+    let path = temp_file("replay.txt");
+    fs::write(&path, "Moves played: 4, 1, 3, 5, 0, 8\n")?;
+
+    // Step through the first two moves, back one and then to the end.
+    tic_tac_toe()
+        .arg("replay")
+        .arg(&path)
+        .write_stdin("\nn\nb\ne\nq\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Start of the game, 6 moves to play",
+        ))
+        .stdout(predicate::str::contains(
+            "Move 1 of 6: X played B2 (4), best (draw)",
+        ))
+        .stdout(predicate::str::contains(
+            "Move 2 of 6: O played B1 (1), blunder (X wins in 6 moves), A1 (0) was best (draw)",
+        ))
+        .stdout(predicate::str::contains("Move 6 of 6: O played C3 (8)"));
+
+    fs::write(&path, "4, 4\n")?;
+    tic_tac_toe()
+        .arg("replay")
+        .arg(&path)
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "move 2 (4) can't be played, square 4 is already taken by X",
+        ));
+
+    // Files that can't be read are a failure while running rather than a
+    // problem with the arguments.
+    tic_tac_toe()
+        .arg("replay")
+        .arg(temp_file("missing_replay.txt"))
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Couldn't read"));
+
+    fs::remove_file(&path)?;
+
+    Ok(())
+}
+
+#[test]
+fn opening_books_are_written_and_checked() -> Result<(), Box<dyn std::error::Error>> {
+    let path = temp_file("openings.book");
+
+    tic_tac_toe()
+        .args([
+            "book",
+            path.to_str().unwrap(),
+            "--size",
+            "4x4",
+            "--depth",
+            "1",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote 8 positions"));
+
+    tic_tac_toe()
+        .args(["--size", "4x4", "--book", path.to_str().unwrap()])
+        .write_stdin("2\n\n\nhard\nn\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Computer plays"));
+
+    // The book was made for a 4x4 board.
+    tic_tac_toe()
+        .args(["--book", path.to_str().unwrap()])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "the book is for 4x4 with 3 in a row but the game is 3x3 with 3 in a row",
+        ));
+
+    let mut bytes = fs::read(&path)?;
+    bytes[20] ^= 1;
+    fs::write(&path, bytes)?;
+    tic_tac_toe()
+        .args(["--size", "4x4", "--book", path.to_str().unwrap()])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("checksum doesn't match"));
+
+    fs::remove_file(&path)?;
+
+    Ok(())
+}
+
+#[test]
+fn completing_a_line_loses_in_misere() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    cmd.args(["--variant", "misere"])
+        .write_stdin("1\n0\n3\n1\n4\n2\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("whoever makes a line loses"))
+        .stdout(predicate::str::contains("1 [X]|[X]|[X]"))
+        .stdout(predicate::str::contains("O wins!"));
+
+    Ok(())
+}
+
+#[test]
+fn numerical_lines_add_up_to_15() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    // X's 1 and 9 need a 5 along the top row, after O tries an odd number
+    // and X tries a used one.
+    cmd.args(["--variant", "numerical"])
+        .write_stdin("1\nA1 1\nB2 3\nB2 2\nB1 1\nB1 9\nC3 4\nC1 5\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "O to play, choose a square and one of 2, 4, 6, 8",
+        ))
+        .stdout(predicate::str::contains(
+            "3 on 4 can't be played, 3 isn't one of your numbers",
+        ))
+        .stdout(predicate::str::contains(
+            "1 on 1 can't be played, 1 has already been used",
+        ))
+        .stdout(predicate::str::contains("1 [1]|[9]|[5]"))
+        .stdout(predicate::str::contains("X wins!"));
+
+    Ok(())
+}
+
+#[test]
+fn numerical_needs_a_3x3_board() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    cmd.args(["--variant", "numerical", "--size", "4x4"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("only played on a 3x3 board"));
+
+    Ok(())
+}
+
+#[test]
+fn variants_are_only_played_from_the_menu() -> Result<(), Box<dyn std::error::Error>> {
+    let modes: [&[&str]; 4] = [
+        &["--tui"],
+        &["serve"],
+        &["join"],
+        &["analyze", "X../.O./..."],
+    ];
+
+    for mode in modes {
+        tic_tac_toe()
+            .args(["--variant", "misere"])
+            .args(mode)
+            .assert()
+            .failure()
+            .code(2)
+            .stderr(predicate::str::contains(
+                "--variant only works with the menu",
+            ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn ultimate_can_be_played_from_the_menu() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = tic_tac_toe();
+
+    // X takes the middle row of the top three boards while O sends X back to
+    // them, after O first tries the wrong board.
+    cmd.write_stdin(concat!(
+        "4\n",
+        "0 3\n",
+        "0 0\n",
+        "A1\n",
+        "4\n0\n5\n1\n",
+        "3\n1\n4\n1\n5\n2\n",
+        "3\n2\n4\n2\n5\n",
+    ))
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("4) Play Ultimate Tic Tac Toe"))
+    .stdout(predicate::str::contains("X to play on any open board"))
+    .stdout(predicate::str::contains(
+        "O to play on board A2 (3), choose a square",
+    ))
+    .stdout(predicate::str::contains(
+        "Can't play there, you have to play on board A2 (3)",
+    ))
+    .stdout(predicate::str::contains("Boards won:"))
+    .stdout(predicate::str::contains("1 [X]|[X]|[X]"))
+    .stdout(predicate::str::contains("X wins!"));
+
+    Ok(())
+}
+
+/// Starts `tic_tac_toe serve` on a free port, returning the server, its
+/// output and the address it's listening on.
+fn start_server() -> (Child, BufReader<ChildStdout>, String) {
+    let mut server = process::Command::new(assert_cmd::cargo::cargo_bin("tic_tac_toe"))
+        .args(["serve", "127.0.0.1:0"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to start the server");
+
+    let mut output = BufReader::new(server.stdout.take().unwrap());
+    let line = wait_for(&mut output, "Listening on ");
+    let address = line.trim_start_matches("Listening on ").to_string();
+
+    (server, output, address)
+}
+
+/// Starts a client in the background with all of its moves already typed.
+fn spawn_client(address: &str, input: &str) -> Child {
+    let mut client = process::Command::new(assert_cmd::cargo::cargo_bin("tic_tac_toe"))
+        .args(["join", address])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to start a client");
+
+    let mut stdin = client.stdin.take().unwrap();
+    stdin.write_all(input.as_bytes()).unwrap();
+
+    client
+}
+
+/// Reads lines from the server until one starts with `prefix`.
+fn wait_for(output: &mut BufReader<ChildStdout>, prefix: &str) -> String {
+    let mut line = String::new();
+    while !line.starts_with(prefix) {
+        line.clear();
+        assert!(
+            output.read_line(&mut line).unwrap() > 0,
+            "server exited before printing '{}'",
+            prefix
+        );
+    }
+    line.trim_end().to_string()
+}
+
+#[test]
+fn two_clients_can_play_over_the_network() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut server, mut output, address) = start_server();
+
+    // The first client to connect plays X.
+    let cross = spawn_client(&address, "0\n1\n2\n");
+    wait_for(&mut output, "X connected");
+
+    tic_tac_toe()
+        .args(["join", &address])
+        .write_stdin("0\n3\nB2\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("you are O"))
+        .stdout(predicate::str::contains(
+            "Can't play there, square 0 is already taken by X",
+        ))
+        .stdout(predicate::str::contains("1 [X]|[X]|[X]"))
+        .stdout(predicate::str::contains("X wins!"));
+
+    let cross = cross.wait_with_output()?;
+    let cross_output = String::from_utf8(cross.stdout)?;
+    assert!(cross.status.success());
+    assert!(cross_output.contains("you are X"));
+    assert!(cross_output.contains("Waiting for O to play..."));
+    assert!(cross_output.contains("X wins!"));
+
+    wait_for(&mut output, "X wins!");
+    assert!(server.wait()?.success());
+
+    Ok(())
+}
+
+#[test]
+fn leaving_a_network_game_tells_the_opponent() -> Result<(), Box<dyn std::error::Error>> {
+    let (mut server, mut output, address) = start_server();
+
+    let cross = spawn_client(&address, "4\n");
+    wait_for(&mut output, "X connected");
+
+    // O runs out of input on its first turn, which quits the game.
+    tic_tac_toe()
+        .args(["join", &address])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("O to play"));
+
+    let cross = cross.wait_with_output()?;
+    assert!(String::from_utf8(cross.stdout)?.contains("Your opponent left the game"));
+
+    wait_for(&mut output, "O left the game");
+    assert!(server.wait()?.success());
+
+    Ok(())
+}
+
+#[test]
+fn joining_without_a_server_fails() -> Result<(), Box<dyn std::error::Error>> {
+    // Bind a port then free it so nothing is listening there.
+    let address = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+
+    tic_tac_toe()
+        .args(["join", &address.to_string()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Couldn't play on"));
 
-    let assert = cmd.stdin(Stdio::from("1")).assert();
     Ok(())
 }
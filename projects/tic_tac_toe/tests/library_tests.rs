@@ -0,0 +1,130 @@
+use std::error::Error;
+use tic_tac_toe::analysis::{Solver, Verdict};
+use tic_tac_toe::variant::{Misere, Variant};
+use tic_tac_toe::{
+    computer, notation, Computer, Difficulty, Game, GameStatus, NotationError, PlayError, Player,
+    PositionError, Rules, RulesError,
+};
+
+fn play_moves(game: &mut Game, moves: &[usize]) {
+    for index in moves {
+        assert!(game.play(*index).is_ok(), "{} should be playable", index);
+    }
+}
+
+#[test]
+fn games_can_be_played_through_the_library() {
+    let mut game = Game::start();
+    play_moves(&mut game, &[0, 3, 1, 4, 2]);
+
+    assert_eq!(
+        game.status(),
+        GameStatus::Won {
+            by: Player::Cross,
+            line: vec![0, 1, 2]
+        }
+    );
+    assert_eq!(game.play(8), Err(PlayError::GameOver));
+
+    assert_eq!(game.undo(), Some(2));
+    assert_eq!(
+        game.play(3),
+        Err(PlayError::SquareTaken {
+            index: 3,
+            by: Player::Naught
+        })
+    );
+    assert_eq!(
+        game.play(9).unwrap_err().to_string(),
+        "square 9 is off the board, which has squares 0 to 8"
+    );
+    assert_eq!(
+        game.status(),
+        GameStatus::InProgress {
+            next: Player::Cross
+        }
+    );
+}
+
+#[test]
+fn errors_work_with_the_question_mark_operator() -> Result<(), Box<dyn Error>> {
+    let mut game = notation::parse("3x3:3 X../.O./... X 0,4 -")?;
+    game.play(8)?;
+
+    let error = notation::parse("3x3:3 XX./O../... X 3 -").unwrap_err();
+    assert_eq!(
+        error.source().map(|source| source.to_string()),
+        Some(String::from(
+            "2 crosses and 0 naughts can't happen with players taking turns"
+        ))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn rules_are_checked_when_games_are_made() {
+    let game = Game::with_rules(7, 6, 4).unwrap();
+
+    assert_eq!(game.rules(), Rules::new(7, 6, 4).unwrap());
+    assert_eq!(game.available_moves().len(), 42);
+    assert_eq!(
+        Game::with_rules(0, 3, 3).err(),
+        Some(RulesError::EmptyBoard)
+    );
+    assert_eq!(
+        Game::with_rules(3, 3, 4).err(),
+        Some(RulesError::WinLengthDoesNotFit)
+    );
+}
+
+#[test]
+fn games_round_trip_through_the_notation() {
+    let mut game = Game::with_rules(4, 4, 3).unwrap();
+    play_moves(&mut game, &[5, 0, 10]);
+    game.undo();
+
+    let text = notation::to_string(&game);
+    assert_eq!(text, "4x4:3 O.../.X../..../.... X 5,0 10");
+    assert_eq!(notation::parse(&text), Ok(game));
+
+    assert_eq!(
+        notation::parse("3x3:3 XX./.../... X - -"),
+        Err(NotationError::InvalidPosition(PositionError::PieceCounts {
+            crosses: 2,
+            naughts: 0
+        }))
+    );
+}
+
+#[test]
+fn the_computer_takes_a_winning_move() {
+    let mut game = Game::start();
+    play_moves(&mut game, &[0, 3, 1, 4]);
+
+    let mut computer = Computer::with_seed(Difficulty::Hard, 1);
+    assert_eq!(computer.choose_move(&game), Some(2));
+    assert_eq!(computer::best_move(&game), Some(2));
+}
+
+#[test]
+fn the_computer_plays_the_variants() {
+    // Completing the top row would lose in misère.
+    let mut game = Misere::new(Rules::classic());
+    for index in [0, 4, 1, 8] {
+        game.apply(index).ok();
+    }
+
+    assert_ne!(computer::best_move(&game), Some(2));
+}
+
+#[test]
+fn the_solver_finds_the_result_with_perfect_play() {
+    let mut solver = Solver::new();
+
+    assert_eq!(solver.evaluate(&Game::start()).verdict, Verdict::Draw);
+
+    let mut game = Game::start();
+    play_moves(&mut game, &[4, 1]);
+    assert_eq!(solver.evaluate(&game).verdict, Verdict::Win);
+}
@@ -0,0 +1,336 @@
+//! Random inputs thrown at every public entry point of the engine, checking
+//! nothing panics and that games stay consistent however they're driven.
+
+use proptest::prelude::*;
+use std::sync::OnceLock;
+use tic_tac_toe::analysis::Solver;
+use tic_tac_toe::book::Book;
+use tic_tac_toe::computer;
+use tic_tac_toe::coord::{column_name, Coord, Square};
+use tic_tac_toe::symmetry::{Canonical, Transform};
+use tic_tac_toe::ultimate::Ultimate;
+use tic_tac_toe::variant::{Misere, NumberMove, Numerical, Variant, Wild, WildMove};
+use tic_tac_toe::{notation, Game, GameStatus, Player, Rules, Symbol};
+
+#[derive(Clone, Debug)]
+enum Action {
+    Play(usize),
+    PlayAt(usize, usize),
+    PlayAs(Player, usize),
+    Undo,
+    Redo,
+}
+
+fn player() -> impl Strategy<Value = Player> {
+    prop_oneof![Just(Player::Cross), Just(Player::Naught)]
+}
+
+fn rules() -> impl Strategy<Value = Rules> {
+    (1..7usize, 1..7usize, 1..7usize).prop_filter_map("win length doesn't fit", |(w, h, k)| {
+        Rules::new(w, h, k).ok()
+    })
+}
+
+fn transform() -> impl Strategy<Value = Transform> {
+    prop::sample::select(Transform::ALL.to_vec())
+}
+
+/// A game on `rules` after trying each of `moves`, some of which may not
+/// be legal.
+fn after_moves(rules: Rules, moves: &[usize]) -> Game {
+    let mut game = Game::starting_with(rules, Player::Cross);
+    for index in moves {
+        let _ = game.play(*index);
+    }
+    game
+}
+
+/// Games small enough for the solver to finish, part way through.
+fn small_game() -> impl Strategy<Value = Game> {
+    let rules = (1..4usize, 1..4usize, 1..4usize)
+        .prop_filter_map("win length doesn't fit", |(w, h, k)| {
+            Rules::new(w, h, k).ok()
+        });
+    (rules, prop::collection::vec(0..9usize, 0..9))
+        .prop_map(|(rules, moves)| after_moves(rules, &moves))
+}
+
+/// A classic opening book, generated once as it takes a while.
+fn book() -> &'static Book {
+    static BOOK: OnceLock<Book> = OnceLock::new();
+    BOOK.get_or_init(|| Book::generate(Rules::classic(), 2))
+}
+
+/// Squares mostly on the board, with some just off it.
+fn action() -> impl Strategy<Value = Action> {
+    prop_oneof![
+        6 => (0..40usize).prop_map(Action::Play),
+        1 => (0..8usize, 0..8usize).prop_map(|(row, col)| Action::PlayAt(row, col)),
+        1 => (player(), 0..40usize).prop_map(|(player, index)| Action::PlayAs(player, index)),
+        2 => Just(Action::Undo),
+        1 => Just(Action::Redo),
+    ]
+}
+
+fn count(game: &Game, symbol: Symbol) -> usize {
+    game.board()
+        .iter()
+        .filter(|square| **square == symbol)
+        .count()
+}
+
+/// What must hold for any game driven only by taking turns, undo and redo.
+fn assert_consistent(game: &Game, first: Player) {
+    let moves = game.history().len();
+    assert_eq!(
+        count(game, Symbol::Cross) + count(game, Symbol::Naught),
+        moves
+    );
+
+    let expected = if moves.is_multiple_of(2) {
+        first
+    } else {
+        first.other()
+    };
+    assert_eq!(game.current_player(), expected);
+
+    match game.status() {
+        GameStatus::Won { by, line } => {
+            assert_eq!(Some(by), game.get_winner());
+            assert_eq!(by, game.current_player().other(), "the winner moved last");
+            assert!(line.len() >= game.rules().win_length());
+            assert!(line
+                .iter()
+                .all(|index| game.board()[*index] == Symbol::from(by)));
+        }
+        GameStatus::Draw => assert!(game.available_moves().is_empty()),
+        GameStatus::InProgress { next } => {
+            assert_eq!(next, game.current_player());
+            assert!(!game.available_moves().is_empty());
+        }
+    }
+
+    assert_eq!(
+        notation::parse(&notation::to_string(game)).as_ref(),
+        Ok(game)
+    );
+}
+
+proptest! {
+    #[test]
+    fn any_sequence_of_turns_keeps_the_game_consistent(
+        rules in rules(),
+        first in player(),
+        actions in prop::collection::vec(action(), 0..60),
+    ) {
+        let mut game = Game::starting_with(rules, first);
+
+        for action in actions {
+            let before = game.clone();
+            let result = match action {
+                Action::Play(index) => game.play(index),
+                Action::PlayAt(row, col) => game.play_at(row, col),
+                Action::Undo => {
+                    game.undo();
+                    Ok(())
+                }
+                Action::Redo => {
+                    game.redo();
+                    Ok(())
+                }
+                // Covered separately, since playing out of turn breaks the
+                // alternation checked here.
+                Action::PlayAs(..) => Ok(()),
+            };
+
+            if result.is_err() {
+                prop_assert_eq!(&game, &before, "a rejected move changed the game");
+            }
+            assert_consistent(&game, first);
+        }
+    }
+
+    #[test]
+    fn playing_as_either_player_never_panics(
+        rules in rules(),
+        actions in prop::collection::vec(action(), 0..60),
+    ) {
+        let mut game = Game::starting_with(rules, Player::Cross);
+
+        for action in actions {
+            let result = match action {
+                Action::Play(index) => game.play(index),
+                Action::PlayAt(row, col) => game.play_at(row, col),
+                Action::PlayAs(player, index) => game.play_as(player, index),
+                Action::Undo => {
+                    game.undo();
+                    Ok(())
+                }
+                Action::Redo => {
+                    game.redo();
+                    Ok(())
+                }
+            };
+            if let Err(error) = result {
+                prop_assert!(!error.to_string().is_empty());
+            }
+            let _ = (game.status(), game.to_string());
+        }
+    }
+
+    #[test]
+    fn rules_of_any_size_are_accepted_or_rejected(
+        width in any::<usize>(),
+        height in any::<usize>(),
+        win_length in any::<usize>(),
+    ) {
+        if let Ok(game) = Game::with_rules(width, height, win_length) {
+            prop_assert_eq!(game.available_moves().len(), width * height);
+        }
+    }
+
+    #[test]
+    fn any_text_can_be_parsed_without_panicking(text in "\\PC{0,40}") {
+        let _ = notation::parse(&text);
+        let _ = text.parse::<Game>();
+        let _ = text.parse::<Coord>();
+        let _ = text.parse::<Square>();
+    }
+
+    #[test]
+    fn notation_like_text_is_parsed_without_panicking(
+        text in "[0-9]{1,2}x[0-9]{1,2}:[0-9] [XO./]{0,20} [XO] [0-9,-]{0,12} [0-9,-]{0,6}",
+    ) {
+        if let Ok(game) = notation::parse(&text) {
+            prop_assert_eq!(notation::parse(&notation::to_string(&game)), Ok(game));
+        }
+    }
+
+    #[test]
+    fn coordinates_of_any_size_can_be_written(row in any::<usize>(), col in any::<usize>()) {
+        let written = Coord { row, col }.to_string();
+
+        prop_assert!(written.starts_with(&column_name(col)));
+    }
+
+    #[test]
+    fn transforms_move_squares_on_the_board_or_refuse(
+        rules in rules(),
+        transform in transform(),
+        index in 0..40usize,
+    ) {
+        let fits = Transform::for_rules(rules).contains(&transform);
+
+        match transform.apply(index, rules) {
+            Some(moved) => {
+                prop_assert!(fits && index < rules.squares());
+                prop_assert!(moved < rules.squares());
+                prop_assert_eq!(transform.inverse().apply(moved, rules), Some(index));
+            }
+            None => prop_assert!(!fits || index >= rules.squares()),
+        }
+    }
+
+    #[test]
+    fn transformed_positions_share_a_canonical_form(
+        rules in rules(),
+        transform in transform(),
+        moves in prop::collection::vec(0..36usize, 0..12),
+    ) {
+        let game = after_moves(rules, &moves);
+        let board = match transform.apply_to_board(game.board(), rules) {
+            Some(board) => board,
+            None => {
+                prop_assert!(!Transform::for_rules(rules).contains(&transform));
+                return Ok(());
+            }
+        };
+        let moved = Game::from_board_starting_with(rules, board, Player::Cross).unwrap();
+
+        let canonical = Canonical::of(&game);
+        prop_assert_eq!(&Canonical::of(&moved).board, &canonical.board);
+        for index in game.available_moves() {
+            let there = canonical.move_to_canonical(index).unwrap();
+            prop_assert_eq!(canonical.board[there], Symbol::Empty);
+            prop_assert_eq!(canonical.move_from_canonical(there), Some(index));
+        }
+        prop_assert_eq!(canonical.move_to_canonical(rules.squares()), None);
+    }
+
+    #[test]
+    fn the_solver_picks_a_move_as_good_as_the_position(game in small_game()) {
+        let mut solver = Solver::new();
+        let evaluation = solver.evaluate(&game);
+
+        match (solver.best_move(&game), game.status()) {
+            (Some(best), GameStatus::InProgress { .. }) => {
+                prop_assert!(game.available_moves().contains(&best));
+                prop_assert_eq!(solver.evaluate_move(&game, best), Some(evaluation));
+            }
+            (None, GameStatus::InProgress { .. }) => prop_assert!(false, "no move in {}", game),
+            (best, _) => prop_assert_eq!(best, None),
+        }
+    }
+
+    #[test]
+    fn the_computer_always_plays_a_legal_move(
+        rules in rules(),
+        moves in prop::collection::vec(0..36usize, 0..36),
+    ) {
+        let game = after_moves(rules, &moves);
+
+        match (computer::best_move(&game), game.status()) {
+            (Some(best), GameStatus::InProgress { .. }) => {
+                prop_assert!(game.available_moves().contains(&best))
+            }
+            (None, GameStatus::InProgress { .. }) => prop_assert!(false, "no move in {}", game),
+            (best, _) => prop_assert_eq!(best, None),
+        }
+    }
+
+    #[test]
+    fn book_moves_are_legal(moves in prop::collection::vec(0..9usize, 0..9)) {
+        let game = after_moves(Rules::classic(), &moves);
+
+        if let Some(best) = book().best_move(&game) {
+            prop_assert!(game.available_moves().contains(&best));
+        }
+    }
+
+    #[test]
+    fn damaged_books_are_rejected(index in any::<prop::sample::Index>(), flip in 1..=255u8) {
+        let mut bytes = book().to_bytes();
+        let index = index.index(bytes.len());
+        bytes[index] ^= flip;
+
+        prop_assert!(Book::from_bytes(&bytes, Rules::classic()).is_err());
+    }
+
+    #[test]
+    fn any_bytes_can_be_read_as_a_book_without_panicking(
+        bytes in prop::collection::vec(any::<u8>(), 0..64),
+    ) {
+        let _ = Book::from_bytes(&bytes, Rules::classic());
+        let _ = Book::from_bytes(&[b"TTTB\x01".to_vec(), bytes].concat(), Rules::classic());
+    }
+
+    #[test]
+    fn the_variants_never_panic(
+        moves in prop::collection::vec((0..12usize, 0..12usize, player()), 0..40),
+    ) {
+        let mut misere = Misere::new(Rules::classic());
+        let mut wild = Wild::new(Rules::classic());
+        let mut numerical = Numerical::new();
+        let mut ultimate = Ultimate::start();
+
+        for (a, b, symbol) in moves {
+            let _ = misere.apply(a);
+            let _ = wild.apply(WildMove { square: a, symbol });
+            let _ = numerical.apply(NumberMove { square: a, number: b as u8 });
+            let _ = ultimate.play(a, b).map_err(|error| error.to_string());
+
+            let _ = (misere.status(), wild.status(), numerical.status(), ultimate.status());
+            let _ = (misere.to_string(), wild.to_string(), numerical.to_string(), ultimate.to_string());
+        }
+    }
+}